@@ -1,23 +1,49 @@
-use clap::Parser;
+use crate::utils::deadline::Deadline;
+use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[clap(version = "0.2.0", author = "Ricky")]
 pub struct Cli {
-    /// 是否发送邮件（需要配合 -n 使用）
-    #[clap(short = 's', long = "send", requires = "homework_name")]
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+
+    /// 是否发送邮件（需要指定作业）
+    #[clap(short = 's', long = "send", global = true)]
     pub send: bool,
 
-    /// 作业名称（需要配合 -s 使用）
-    #[clap(short = 'n', long = "name", requires = "send")]
+    /// 作业ID或名称（发送邮件时必填，接收邮件时作为下载子目录），配置了 [[homework]] 时可用 check 子命令代替
+    #[clap(short = 'n', long = "name")]
     pub homework_name: Option<String>,
 
-    /// 小组数量 TODO 检查组号，找出未提交的组
-    #[clap(short = 'g', long = "cnt", default_value = "0")]
-    pub group_cnt: i32,
+    /// 作业截止时间，如 "2024-10-01 23:59"，覆盖配置中该作业的截止时间
+    #[clap(
+        long = "deadline",
+        value_name = "DEADLINE",
+        value_parser = Deadline::parse,
+        global = true
+    )]
+    pub deadline: Option<Deadline>,
+
+    /// 演练模式：只生成并打印提醒邮件，不连接SMTP服务器
+    #[clap(long = "dry-run", requires = "send", global = true)]
+    pub dry_run: bool,
+
+    /// 演练模式下将每封邮件保存为 .eml 文件的目录
+    #[clap(
+        long = "eml-dir",
+        value_name = "DIR",
+        requires = "dry_run",
+        global = true
+    )]
+    pub eml_dir: Option<PathBuf>,
+
+    /// 小组数量，大于0时按文件名中的组号检查小组作业，找出未提交的组
+    #[clap(short = 'g', long = "cnt", default_value = "0", global = true)]
+    pub group_cnt: u32,
 
     /// 是否接收并下载邮件到指定目录
-    #[clap(short = 'r', long = "resv")]
+    #[clap(short = 'r', long = "resv", global = true)]
     pub resv: bool,
 
     /// 配置文件目录路径
@@ -25,15 +51,64 @@ pub struct Cli {
         short = 'c',
         long = "config",
         value_name = "DIR",
-        default_value = "cfg"
+        default_value = "cfg",
+        global = true
     )]
     pub config_dir: PathBuf,
 
     /// 环境（dev, prod）
-    #[clap(short = 'e', long = "env", value_name = "ENV", default_value = "dev")]
+    #[clap(
+        short = 'e',
+        long = "env",
+        value_name = "ENV",
+        default_value = "dev",
+        global = true
+    )]
     pub env: String,
 
-    /// 作业检查目录，默认为当前目录
-    #[clap(short = 'd', long = "dir", value_name = "CHECK_DIR")]
+    /// 作业检查目录，默认为作业定义中的目录或当前目录
+    #[clap(short = 'd', long = "dir", value_name = "CHECK_DIR", global = true)]
     pub check_dir: Option<PathBuf>,
+
+    /// 列出已提交学生的文件，以及压缩包和文件夹中的文件
+    #[clap(short = 'l', long = "list", global = true)]
+    pub list: bool,
+
+    /// 将每名已提交学生的文件整理到该目录下的 <学号_姓名> 子目录，压缩包会被解压
+    #[clap(short = 'x', long = "extract", value_name = "DIR", global = true)]
+    pub extract_dir: Option<PathBuf>,
+}
+
+/// 子命令
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// 管理配置文件
+    Config {
+        #[clap(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// 按配置中的 [[homework]] 定义检查作业，如 `hsck check hw3`
+    Check {
+        /// 作业ID或名称
+        #[clap(value_name = "HOMEWORK")]
+        homework: String,
+    },
+}
+
+/// `config` 子命令
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// 交互式生成 default/dev/prod/local 配置文件
+    Init {
+        /// 覆盖已存在的配置文件
+        #[clap(long = "force")]
+        force: bool,
+    },
+
+    /// 打印合并后的配置（密码已隐藏）及每个配置项的来源
+    Show,
+
+    /// 校验配置，存在问题时以非零状态退出
+    Validate,
 }
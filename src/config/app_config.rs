@@ -1,37 +1,125 @@
-use serde::Deserialize;
-
-use super::stu::Stu;
+use config::Config;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::roster::{self, RosterFile};
+use super::stu::{Stu, EMAIL_REGEX};
+use super::validation::{ConfigValidationError, ConfigValidationReport, Validation};
+use crate::email::subject_filter::SubjectFilter;
+use crate::utils::archive::RequiredFiles;
+use crate::utils::content::parse_size;
+use crate::utils::deadline::Deadline;
+use crate::utils::filename_pattern::FilenamePattern;
+use crate::utils::scanner::build_glob_set;
 
 /// 应用配置
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AppConfig {
+    /// 是否严格校验，未设置时由环境决定（prod环境严格校验）
+    #[serde(default)]
+    pub strict: Option<bool>,
+
     pub smtp_config: SmtpConfig,
     pub imap_config: ImapConfig,
     pub stu_config: StuConfig,
     pub log_config: LogConfig,
+
+    #[serde(default)]
+    pub template_config: TemplateConfig,
+
+    #[serde(default)]
+    pub check_config: CheckConfig,
+
+    /// 作业定义
+    #[serde(default)]
+    pub homework: Vec<Homework>,
+
+    /// 加载配置时发现的警告
+    #[serde(skip)]
+    pub warnings: Vec<ConfigValidationError>,
 }
 
 impl AppConfig {
     /// 验证整个配置
-    pub fn validate(&self) -> Result<(), String> {
+    ///
+    /// 严格程度由配置中的 `strict` 选项或当前环境决定，所有问题一并返回
+    ///
+    /// # param
+    /// * `environment` - 当前环境（APP_ENV）
+    /// * `origins` - 合并后的原始配置，用于在报告中标明配置项的来源文件
+    ///
+    /// # return
+    /// * `Result<Vec<ConfigValidationError>, ConfigValidationReport>` - 验证通过时返回所有警告，
+    ///   否则返回包含所有问题的报告
+    pub fn validate(
+        &self,
+        environment: &str,
+        origins: Option<&Config>,
+    ) -> Result<Vec<ConfigValidationError>, ConfigValidationReport> {
+        let mut validation = Validation::for_environment(environment, self.strict);
+        if let Some(origins) = origins {
+            validation = validation.with_origins(origins);
+        }
+
         // 验证SMTP配置
-        self.smtp_config.validate()?;
+        self.smtp_config.validate(&mut validation);
 
         // 验证IMAP配置
-        self.imap_config.validate()?;
+        self.imap_config.validate(&mut validation);
 
         // 验证学生配置
-        self.stu_config.validate()?;
+        self.stu_config.validate(&mut validation);
 
         // 验证日志配置
-        self.log_config.validate()?;
+        self.log_config.validate(&mut validation);
+
+        // 验证邮件模板配置
+        self.template_config.validate(&mut validation);
+
+        // 验证作业检查配置
+        self.check_config.validate(&mut validation);
+
+        // 验证作业定义
+        let mut ids: HashMap<&str, usize> = HashMap::new();
+        for (index, homework) in self.homework.iter().enumerate() {
+            homework.validate(&mut validation, index);
+            match ids.entry(homework.id.as_str()) {
+                Entry::Occupied(first) => {
+                    let location = validation.at(&format!("homework[{}].id", index));
+                    validation.report(ConfigValidationError::DuplicateHomework {
+                        location,
+                        id: homework.id.clone(),
+                        first: format!("homework[{}]", first.get()),
+                    });
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(index);
+                }
+            }
+        }
 
-        Ok(())
+        validation.finish()
+    }
+
+    /// 按ID或名称查找作业定义
+    ///
+    /// # param
+    /// * `key` - 作业ID或名称
+    ///
+    /// # return
+    /// * `Option<&Homework>` - 作业定义，未定义时为None
+    pub fn find_homework(&self, key: &str) -> Option<&Homework> {
+        self.homework
+            .iter()
+            .find(|homework| homework.id == key)
+            .or_else(|| self.homework.iter().find(|homework| homework.name == key))
     }
 }
 
 /// SMTP配置
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SmtpConfig {
     #[serde(default = "default_smtp_server")]
     pub server: String,
@@ -46,25 +134,55 @@ pub struct SmtpConfig {
     pub password: String,
 
     pub encryption: Encryption,
+
+    /// 异步发送时同时使用的最大连接数
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+
+    /// 每分钟最多发送的邮件数，0表示不限制
+    #[serde(default = "default_rate_limit_per_minute")]
+    pub rate_limit_per_minute: u32,
+
+    /// 遇到暂时性错误时每封邮件最多尝试的次数（包括第一次发送）
+    #[serde(default = "default_retry_attempts")]
+    pub retry_attempts: u32,
+
+    /// 第一次重试前等待的毫秒数，之后每次重试翻倍
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
 }
 
 impl SmtpConfig {
     /// 验证SMTP配置
-    pub fn validate(&self) -> Result<(), String> {
-        if self.server.is_empty() {
-            return Err("SMTP server cannot be empty".into());
+    pub fn validate(&self, validation: &mut Validation) {
+        use ConfigValidationError::*;
+
+        validation.ensure(!self.server.is_empty(), "smtp_config.server", Empty);
+        validation.ensure(self.port > 0, "smtp_config.port", NotPositive);
+        validation.ensure(
+            self.max_concurrency > 0,
+            "smtp_config.max_concurrency",
+            NotPositive,
+        );
+        validation.ensure(
+            self.retry_attempts > 0,
+            "smtp_config.retry_attempts",
+            NotPositive,
+        );
+
+        // 严格校验时，凭据是必需的
+        if validation.is_strict() {
+            validation.ensure(
+                !self.username.is_empty(),
+                "smtp_config.username",
+                RequiredInStrictMode,
+            );
+            validation.ensure(
+                !self.password.is_empty(),
+                "smtp_config.password",
+                RequiredInStrictMode,
+            );
         }
-
-        if self.port == 0 {
-            return Err("SMTP port must be greater than 0".into());
-        }
-
-        // 在生产环境中，凭据是必需的
-        if cfg!(not(debug_assertions)) && (self.username.is_empty() || self.password.is_empty()) {
-            return Err("SMTP username and password are required in production".into());
-        }
-
-        Ok(())
     }
 }
 
@@ -76,17 +194,41 @@ fn default_smtp_port() -> u16 {
     465
 }
 
+fn default_max_concurrency() -> usize {
+    2
+}
+
+fn default_rate_limit_per_minute() -> u32 {
+    30
+}
+
+fn default_retry_attempts() -> u32 {
+    3
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    2000
+}
+
 /// 加密方式
-#[derive(Debug, Clone, Copy, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Encryption {
+    /// 隐式TLS，连接建立后立即握手（通常为465/993端口）
     Tls,
+
+    /// 必须通过STARTTLS升级为加密连接（通常为587/143端口）
     StartTls,
+
+    /// 服务器支持时使用STARTTLS，否则使用明文连接
+    Opportunistic,
+
+    /// 明文连接，仅适用于本地中继
     None,
 }
 
 /// IMAP配置
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ImapConfig {
     #[serde(default = "default_imap_server")]
     pub server: String,
@@ -100,31 +242,50 @@ pub struct ImapConfig {
     #[serde(default)]
     pub password: String,
 
+    #[serde(default = "default_imap_encryption")]
+    pub encryption: Encryption,
+
+    #[serde(default = "default_mailbox")]
+    pub mailbox: String,
+
+    /// 邮件主题模式，`{homework}` 会被替换为作业名称，以 `regex:` 开头时按正则表达式处理
+    #[serde(default = "default_subject_pattern")]
+    pub subject_pattern: String,
+
     #[serde(default = "default_output_dir")]
     pub out_dir: String,
 }
 
 impl ImapConfig {
     /// 验证IMAP配置
-    pub fn validate(&self) -> Result<(), String> {
-        if self.server.is_empty() {
-            return Err("IMAP server cannot be empty".into());
+    pub fn validate(&self, validation: &mut Validation) {
+        use ConfigValidationError::*;
+
+        validation.ensure(!self.server.is_empty(), "imap_config.server", Empty);
+        validation.ensure(self.port > 0, "imap_config.port", NotPositive);
+
+        // 严格校验时，凭据是必需的
+        if validation.is_strict() {
+            validation.ensure(
+                !self.username.is_empty(),
+                "imap_config.username",
+                RequiredInStrictMode,
+            );
+            validation.ensure(
+                !self.password.is_empty(),
+                "imap_config.password",
+                RequiredInStrictMode,
+            );
         }
 
-        if self.port == 0 {
-            return Err("IMAP port must be greater than 0".into());
-        }
+        validation.ensure(!self.mailbox.is_empty(), "imap_config.mailbox", Empty);
+        validation.ensure(!self.out_dir.is_empty(), "imap_config.out_dir", Empty);
 
-        // 在生产环境中，凭据是必需的
-        if cfg!(not(debug_assertions)) && (self.username.is_empty() || self.password.is_empty()) {
-            return Err("IMAP username and password are required in production".into());
-        }
-
-        if self.out_dir.is_empty() {
-            return Err("Output directory cannot be empty".into());
-        }
-
-        Ok(())
+        validate_subject_pattern(
+            validation,
+            "imap_config.subject_pattern",
+            &self.subject_pattern,
+        );
     }
 }
 
@@ -136,34 +297,543 @@ fn default_imap_port() -> u16 {
     993
 }
 
+fn default_imap_encryption() -> Encryption {
+    Encryption::Tls
+}
+
+fn default_mailbox() -> String {
+    "INBOX".into()
+}
+
+fn default_subject_pattern() -> String {
+    "*【{homework}】*".into()
+}
+
 fn default_output_dir() -> String {
     "/out".into()
 }
 
+/// 提醒邮件模板配置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TemplateConfig {
+    /// 邮件主题模板
+    #[serde(default = "default_template_subject")]
+    pub subject: String,
+
+    /// 纯文本正文模板文件，相对路径相对于配置目录
+    #[serde(default = "default_text_template_file")]
+    pub text_file: String,
+
+    /// HTML正文模板文件，相对路径相对于配置目录
+    #[serde(default = "default_html_template_file")]
+    pub html_file: String,
+
+    /// 无效提交提醒的邮件主题模板
+    #[serde(default = "default_invalid_subject")]
+    pub invalid_subject: String,
+
+    /// 无效提交提醒的纯文本正文模板文件，相对路径相对于配置目录
+    #[serde(default = "default_invalid_text_file")]
+    pub invalid_text_file: String,
+
+    /// 无效提交提醒的HTML正文模板文件，相对路径相对于配置目录
+    #[serde(default = "default_invalid_html_file")]
+    pub invalid_html_file: String,
+
+    /// 作业提交地址，为空时使用IMAP用户名
+    #[serde(default)]
+    pub submit_to: String,
+}
+
+impl Default for TemplateConfig {
+    fn default() -> Self {
+        Self {
+            subject: default_template_subject(),
+            text_file: default_text_template_file(),
+            html_file: default_html_template_file(),
+            invalid_subject: default_invalid_subject(),
+            invalid_text_file: default_invalid_text_file(),
+            invalid_html_file: default_invalid_html_file(),
+            submit_to: String::new(),
+        }
+    }
+}
+
+impl TemplateConfig {
+    /// 验证邮件模板配置
+    pub fn validate(&self, validation: &mut Validation) {
+        use ConfigValidationError::*;
+
+        validation.ensure(
+            !self.subject.trim().is_empty(),
+            "template_config.subject",
+            Empty,
+        );
+        validation.ensure(
+            !self.text_file.is_empty(),
+            "template_config.text_file",
+            Empty,
+        );
+        validation.ensure(
+            !self.html_file.is_empty(),
+            "template_config.html_file",
+            Empty,
+        );
+        validation.ensure(
+            !self.invalid_subject.trim().is_empty(),
+            "template_config.invalid_subject",
+            Empty,
+        );
+        validation.ensure(
+            !self.invalid_text_file.is_empty(),
+            "template_config.invalid_text_file",
+            Empty,
+        );
+        validation.ensure(
+            !self.invalid_html_file.is_empty(),
+            "template_config.invalid_html_file",
+            Empty,
+        );
+        validation.ensure(
+            self.submit_to.is_empty() || EMAIL_REGEX.is_match(&self.submit_to),
+            "template_config.submit_to",
+            |location| InvalidEmail {
+                location,
+                value: self.submit_to.clone(),
+            },
+        );
+    }
+}
+
+fn default_template_subject() -> String {
+    "作业未提交提醒：{{homework}}".into()
+}
+
+fn default_text_template_file() -> String {
+    "templates/reminder.txt".into()
+}
+
+fn default_html_template_file() -> String {
+    "templates/reminder.html".into()
+}
+
+fn default_invalid_subject() -> String {
+    "作业提交无效提醒：{{homework}}".into()
+}
+
+fn default_invalid_text_file() -> String {
+    "templates/invalid.txt".into()
+}
+
+fn default_invalid_html_file() -> String {
+    "templates/invalid.html".into()
+}
+
+/// 作业检查配置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CheckConfig {
+    /// 提交文件名模式，如 `{id}_{name}_{homework}.{pdf|docx}`；为空时文件名包含学号或姓名即视为已提交
+    #[serde(default)]
+    pub filename_pattern: String,
+
+    /// 是否递归检查子目录中的文件，文件名和所在目录名都可以用来匹配学生
+    #[serde(default)]
+    pub recursive: bool,
+
+    /// 递归检查的最大深度，检查目录下的直接条目深度为1
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+
+    /// 只检查匹配这些通配符的文件，为空时检查所有文件
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    /// 忽略匹配这些通配符的文件和目录
+    #[serde(default = "default_exclude")]
+    pub exclude: Vec<String>,
+}
+
+impl Default for CheckConfig {
+    fn default() -> Self {
+        Self {
+            filename_pattern: String::new(),
+            recursive: false,
+            max_depth: default_max_depth(),
+            include: Vec::new(),
+            exclude: default_exclude(),
+        }
+    }
+}
+
+impl CheckConfig {
+    /// 验证作业检查配置
+    pub fn validate(&self, validation: &mut Validation) {
+        use ConfigValidationError::*;
+
+        validate_filename_pattern(
+            validation,
+            "check_config.filename_pattern",
+            &self.filename_pattern,
+        );
+
+        validation.ensure(
+            !self.recursive || self.max_depth > 0,
+            "check_config.max_depth",
+            NotPositive,
+        );
+
+        let globs = [("include", &self.include), ("exclude", &self.exclude)];
+        for (name, globs) in globs {
+            for (index, glob) in globs.iter().enumerate() {
+                if let Err(e) = build_glob_set(std::slice::from_ref(glob)) {
+                    let location = validation.at(&format!("check_config.{}[{}]", name, index));
+                    validation.report(InvalidPattern {
+                        location,
+                        pattern: glob.clone(),
+                        reason: format!("{:#}", e.root_cause()),
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn default_max_depth() -> usize {
+    5
+}
+
+fn default_exclude() -> Vec<String> {
+    [
+        ".DS_Store",
+        "Thumbs.db",
+        "desktop.ini",
+        "~$*",
+        "._*",
+        "__MACOSX",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+/// 校验文件名模式，为空时不校验
+fn validate_filename_pattern(validation: &mut Validation, key: &str, pattern: &str) {
+    if pattern.is_empty() {
+        return;
+    }
+
+    if let Err(e) = FilenamePattern::from_pattern(pattern, Some("homework")) {
+        let location = validation.at(key);
+        validation.report(ConfigValidationError::InvalidPattern {
+            location,
+            pattern: pattern.to_string(),
+            reason: format!("{:#}", e.root_cause()),
+        });
+    }
+}
+
+/// 校验邮件主题模式
+fn validate_subject_pattern(validation: &mut Validation, key: &str, pattern: &str) {
+    if let Err(e) = SubjectFilter::from_pattern(pattern, "homework") {
+        let location = validation.at(key);
+        validation.report(ConfigValidationError::InvalidPattern {
+            location,
+            pattern: pattern.to_string(),
+            reason: format!("{:#}", e.root_cause()),
+        });
+    }
+}
+
+/// 作业定义
+///
+/// 在配置中以 `[[homework]]` 表声明，`hsck check <id>` 根据它确定检查目录、截止时间和命名规范
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Homework {
+    /// 作业ID，如 `hw3`
+    pub id: String,
+
+    /// 作业名称，用于提醒邮件、邮件主题筛选和下载目录，为空时使用ID
+    #[serde(default)]
+    pub name: String,
+
+    /// 截止时间，如 `2024-10-01 23:59`，为空时不判断迟交
+    #[serde(default)]
+    pub deadline: String,
+
+    /// 提交目录，为空时使用 `-d` 指定的目录或邮件下载目录
+    #[serde(default)]
+    pub dir: String,
+
+    /// 该作业的文件名模式，为空时使用 `check_config.filename_pattern`
+    #[serde(default)]
+    pub filename_pattern: String,
+
+    /// 该作业的邮件主题模式，为空时使用 `imap_config.subject_pattern`
+    #[serde(default)]
+    pub subject_pattern: String,
+
+    /// 允许提交的文件扩展名，如 `["pdf", "docx"]`，为空时不限制
+    #[serde(default)]
+    pub extensions: Vec<String>,
+
+    /// 提交文件的最小大小，如 `10KB`，为空时只拒绝空文件
+    #[serde(default)]
+    pub min_size: String,
+
+    /// 提交文件的最大大小，如 `20MB`，为空时不限制
+    #[serde(default)]
+    pub max_size: String,
+
+    /// 提交中必须包含的文件，如 `["report.pdf", "src/"]`，在压缩包和文件夹中查找
+    #[serde(default)]
+    pub required_files: Vec<String>,
+
+    /// 个人作业或小组作业
+    #[serde(default)]
+    pub kind: HomeworkKind,
+
+    /// 小组作业的小组数量
+    #[serde(default)]
+    pub groups: u32,
+}
+
+/// 作业类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HomeworkKind {
+    /// 个人作业，按学生检查
+    #[default]
+    Individual,
+
+    /// 小组作业，按文件名中的组号检查
+    Group,
+}
+
+impl Homework {
+    /// 为没有在配置中定义的作业创建默认设置
+    ///
+    /// # param
+    /// * `name` - 作业名称（`-n` 参数）
+    pub fn named(name: &str) -> Self {
+        Self {
+            id: name.into(),
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// 作业名称，未设置时为ID
+    pub fn display_name(&self) -> &str {
+        match self.name.is_empty() {
+            true => &self.id,
+            false => &self.name,
+        }
+    }
+
+    /// 提交目录，未设置时为None
+    pub fn submit_dir(&self) -> Option<PathBuf> {
+        Some(self.dir.as_str())
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    }
+
+    /// 截止时间，未设置时为None
+    pub fn deadline(&self) -> anyhow::Result<Option<Deadline>> {
+        Some(self.deadline.as_str())
+            .filter(|deadline| !deadline.is_empty())
+            .map(Deadline::parse)
+            .transpose()
+    }
+
+    /// 验证作业定义
+    fn validate(&self, validation: &mut Validation, index: usize) {
+        use ConfigValidationError::*;
+
+        let key = |field: &str| format!("homework[{}].{}", index, field);
+        validation.ensure(!self.id.trim().is_empty(), &key("id"), Empty);
+        if self.deadline().is_err() {
+            let location = validation.at(&key("deadline"));
+            validation.report(InvalidDeadline {
+                location,
+                value: self.deadline.clone(),
+            });
+        }
+        validate_filename_pattern(validation, &key("filename_pattern"), &self.filename_pattern);
+        if !self.subject_pattern.is_empty() {
+            validate_subject_pattern(validation, &key("subject_pattern"), &self.subject_pattern);
+        }
+        for (field, value) in [("min_size", &self.min_size), ("max_size", &self.max_size)] {
+            if !value.is_empty() && parse_size(value).is_err() {
+                let location = validation.at(&key(field));
+                validation.report(InvalidSize {
+                    location,
+                    value: value.clone(),
+                });
+            }
+        }
+        for (i, pattern) in self.required_files.iter().enumerate() {
+            if let Err(e) = RequiredFiles::new(std::slice::from_ref(pattern)) {
+                let location = validation.at(&key(&format!("required_files[{}]", i)));
+                validation.report(InvalidPattern {
+                    location,
+                    pattern: pattern.clone(),
+                    reason: format!("{:#}", e.root_cause()),
+                });
+            }
+        }
+        validation.ensure(
+            self.kind != HomeworkKind::Group || self.groups > 0,
+            &key("groups"),
+            NotPositive,
+        );
+    }
+}
+
 /// 学生列表配置
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct StuConfig {
+    /// 直接写在配置中的学生
+    #[serde(default)]
     pub list: Vec<Stu>,
+
+    /// 学生名单文件（CSV/Excel），其中的学生在加载时追加到 `list` 之后
+    #[serde(default)]
+    pub files: Vec<RosterFile>,
+
+    /// 从名单文件追加的学生所在位置（`文件:行号`），与 `list` 末尾的学生一一对应
+    #[serde(skip)]
+    imported: Vec<String>,
+
+    /// 读取名单文件时发现的格式错误
+    #[serde(skip)]
+    roster_problems: Vec<ConfigValidationError>,
 }
 
 impl StuConfig {
+    /// 读取所有名单文件，并将其中的学生追加到 `list`
+    ///
+    /// 格式错误的行不会加入名单，留待校验时统一报告
+    ///
+    /// # param
+    /// * `config_dir` - 配置目录，名单文件的相对路径相对于该目录
+    ///
+    /// # return
+    /// * `anyhow::Result<()>` - 名单文件无法读取或缺少必需的列时返回错误
+    pub fn load_files(&mut self, config_dir: &Path) -> anyhow::Result<()> {
+        for file in &self.files {
+            let path = config_dir.join(&file.path);
+            let roster = roster::read(file, &path)?;
+            for row in roster.rows {
+                self.list.push(row.student);
+                self.imported
+                    .push(format!("{}:{}", path.display(), row.line));
+            }
+            self.roster_problems.extend(roster.problems);
+        }
+
+        Ok(())
+    }
+
     /// 验证学生配置
-    pub fn validate(&self) -> Result<(), String> {
-        for student in &self.list {
-            student.check_email()?;
+    pub fn validate(&self, validation: &mut Validation) {
+        // 名单文件中的学生在读取时已检查过邮箱
+        let inline = self.list.len() - self.imported.len();
+        for (index, student) in self.list[..inline].iter().enumerate() {
+            student.check_email(validation, &format!("stu_config.list[{}]", index));
         }
 
-        // 在生产环境中，我们至少应该有一个学生
-        if cfg!(not(debug_assertions)) && self.list.is_empty() {
-            return Err("Student list cannot be empty in production".into());
+        for problem in &self.roster_problems {
+            validation.report(problem.clone());
         }
 
-        Ok(())
+        let positions: Vec<String> = (0..self.list.len())
+            .map(|index| match index.checked_sub(inline) {
+                Some(imported) => self.imported[imported].clone(),
+                None => validation
+                    .at(&format!("stu_config.list[{}]", index))
+                    .to_string(),
+            })
+            .collect();
+
+        // 首尾空白会导致按姓名或学号匹配失败，名单文件中的值在读取时已去除空白
+        for (index, student) in self.list[..inline].iter().enumerate() {
+            let fields = [("name", Some(&student.name)), ("id", student.id.as_ref())];
+            for (field, value) in fields {
+                if let Some(value) = value.filter(|value| value.trim() != value.as_str()) {
+                    let key = format!("stu_config.list[{}].{}", index, field);
+                    validation.flag(ConfigValidationError::SurroundingWhitespace {
+                        position: validation.at(&key).to_string(),
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+
+        // 同一邮箱或学号只能属于一名学生
+        let mut emails: HashMap<String, &String> = HashMap::new();
+        let mut ids: HashMap<&str, &String> = HashMap::new();
+        for (student, position) in self.list.iter().zip(&positions) {
+            match emails.entry(student.email.to_lowercase()) {
+                Entry::Occupied(first) => {
+                    validation.flag(ConfigValidationError::DuplicateStudent {
+                        position: position.clone(),
+                        email: student.email.clone(),
+                        first: first.get().to_string(),
+                    })
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(position);
+                }
+            }
+
+            let Some(id) = student
+                .id
+                .as_deref()
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+            else {
+                continue;
+            };
+            match ids.entry(id) {
+                Entry::Occupied(first) => validation.flag(ConfigValidationError::DuplicateId {
+                    position: position.clone(),
+                    id: id.to_string(),
+                    first: first.get().to_string(),
+                }),
+                Entry::Vacant(entry) => {
+                    entry.insert(position);
+                }
+            }
+        }
+
+        // 姓名是另一名学生姓名的一部分时（如 `王伟` 与 `王伟明`），按姓名匹配文件会把后者的作业算作前者提交
+        for (student, position) in self.list.iter().zip(&positions) {
+            let name = student.name.trim();
+            for (other, other_position) in self.list.iter().zip(&positions) {
+                let other_name = other.name.trim();
+                if !name.is_empty() && other_name != name && other_name.contains(name) {
+                    validation.flag(ConfigValidationError::AmbiguousName {
+                        position: position.clone(),
+                        name: name.to_string(),
+                        other: other_name.to_string(),
+                        other_position: other_position.clone(),
+                    });
+                }
+            }
+        }
+
+        // 严格校验时，我们至少应该有一个学生
+        if validation.is_strict() {
+            validation.ensure(
+                !self.list.is_empty(),
+                "stu_config.list",
+                ConfigValidationError::EmptyRoster,
+            );
+        }
     }
 }
 
 /// 日志配置
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LogConfig {
     #[serde(default = "default_log_level")]
     pub level: String,
@@ -174,17 +844,18 @@ pub struct LogConfig {
 
 impl LogConfig {
     /// 验证日志配置
-    pub fn validate(&self) -> Result<(), String> {
-        let valid_levels = ["error", "warn", "info", "debug", "trace"];
-
-        if !valid_levels.contains(&self.level.to_lowercase().as_str()) {
-            return Err(format!(
-                "Invalid log level: {}. Valid levels are: error, warn, info, debug, trace",
-                self.level
-            ));
-        }
-
-        Ok(())
+    pub fn validate(&self, validation: &mut Validation) {
+        const VALID_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+        validation.ensure(
+            VALID_LEVELS.contains(&self.level.to_lowercase().as_str()),
+            "log_config.level",
+            |location| ConfigValidationError::InvalidChoice {
+                location,
+                value: self.level.clone(),
+                choices: VALID_LEVELS,
+            },
+        );
     }
 }
 
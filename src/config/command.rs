@@ -0,0 +1,411 @@
+use anyhow::{bail, Context, Result};
+use serde_json::Value as JsonValue;
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use super::app_config::AppConfig;
+use super::loader;
+use super::validation::{lookup, ConfigValidationError};
+use crate::email::template::ReminderTemplate;
+
+/// 示例默认配置，`config init` 原样写入
+const DEFAULT_CONFIG: &str = include_str!("../../cfg/default.toml");
+
+/// 示例纯文本提醒模板
+const TEXT_TEMPLATE: &str = include_str!("../../cfg/templates/reminder.txt");
+
+/// 示例HTML提醒模板
+const HTML_TEMPLATE: &str = include_str!("../../cfg/templates/reminder.html");
+
+/// 示例纯文本无效提交提醒模板
+const INVALID_TEXT_TEMPLATE: &str = include_str!("../../cfg/templates/invalid.txt");
+
+/// 示例HTML无效提交提醒模板
+const INVALID_HTML_TEMPLATE: &str = include_str!("../../cfg/templates/invalid.html");
+
+/// 隐藏后的密码
+const REDACTED: &str = "******";
+
+/// 交互式生成配置文件
+///
+/// 依次询问SMTP/IMAP服务器与凭据，直接回车使用方括号中的默认值。生成的文件：
+/// - default.toml - 基础配置
+/// - dev.toml - 开发环境，输出debug日志到控制台
+/// - prod.toml - 生产环境，凭据通过 `${SMTP_PASSWORD}` 等环境变量占位符读取
+/// - local.toml - 本机凭据，并写入配置目录的 `.gitignore`
+/// - templates/ - 提醒邮件模板与无效提交提醒模板
+///
+/// # param
+/// * `dir` - 配置目录，不存在时自动创建
+/// * `force` - 是否覆盖已存在的文件
+/// * `input` - 读取回答的输入
+/// * `output` - 输出提示的目标
+///
+/// # return
+/// * `Result<Vec<PathBuf>>` - 写入的文件列表或错误
+pub fn init(
+    dir: &Path,
+    force: bool,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> Result<Vec<PathBuf>> {
+    const FILES: [&str; 9] = [
+        "default.toml",
+        "dev.toml",
+        "prod.toml",
+        "local.toml",
+        ".gitignore",
+        "templates/reminder.txt",
+        "templates/reminder.html",
+        "templates/invalid.txt",
+        "templates/invalid.html",
+    ];
+
+    let existing: Vec<String> = FILES
+        .iter()
+        .map(|file| dir.join(file))
+        .filter(|path| path.exists())
+        .map(|path| path.display().to_string())
+        .collect();
+    if !force && !existing.is_empty() {
+        bail!(
+            "以下文件已存在，使用 --force 覆盖：\n{}",
+            existing.join("\n")
+        );
+    }
+
+    let mut prompt = Prompt { input, output };
+    let smtp_server = prompt.ask("SMTP服务器", "smtp.163.com")?;
+    let smtp_port = prompt.ask_port("SMTP端口", 465)?;
+    let smtp_username = prompt.ask("发件邮箱（SMTP用户名）", "")?;
+    let smtp_password = prompt.ask("SMTP密码或授权码（仅保存到 local.toml）", "")?;
+    let imap_server = prompt.ask("IMAP服务器", "imap.qq.com")?;
+    let imap_port = prompt.ask_port("IMAP端口", 993)?;
+    let imap_username = prompt.ask("收件邮箱（IMAP用户名）", &smtp_username)?;
+    let imap_password = prompt.ask("IMAP密码或授权码（仅保存到 local.toml）", "")?;
+    let out_dir = prompt.ask("作业下载目录", "/out")?;
+
+    let servers = |username: &str, password: &str, imap_username: &str, imap_password: &str| {
+        format!(
+            "[smtp_config]\nserver = {}\nport = {}\nusername = {}\npassword = {}\n\n\
+             [imap_config]\nserver = {}\nport = {}\nusername = {}\npassword = {}\nout_dir = {}\n",
+            quote(&smtp_server),
+            smtp_port,
+            quote(username),
+            quote(password),
+            quote(&imap_server),
+            imap_port,
+            quote(imap_username),
+            quote(imap_password),
+            quote(&out_dir),
+        )
+    };
+    let dev = "# 开发环境配置\n\n[log_config]\nlevel = \"debug\"\nconsole_output = true\n";
+    let prod = format!(
+        "# 生产环境配置，凭据从环境变量读取\n\n{}\n[log_config]\nlevel = \"info\"\nconsole_output = false\n",
+        servers(
+            "${SMTP_USERNAME}",
+            "${SMTP_PASSWORD}",
+            "${IMAP_USERNAME}",
+            "${IMAP_PASSWORD}"
+        )
+    );
+    let local = format!(
+        "# 本机配置，包含凭据，不要提交到版本控制\n\n{}",
+        servers(
+            &smtp_username,
+            &smtp_password,
+            &imap_username,
+            &imap_password
+        )
+    );
+    let contents = [
+        DEFAULT_CONFIG,
+        dev,
+        &prod,
+        &local,
+        "local.toml\n",
+        TEXT_TEMPLATE,
+        HTML_TEMPLATE,
+        INVALID_TEXT_TEMPLATE,
+        INVALID_HTML_TEMPLATE,
+    ];
+
+    let mut written = Vec::new();
+    for (file, content) in FILES.iter().zip(contents) {
+        let path = dir.join(file);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("无法创建目录: {}", parent.display()))?;
+        }
+        fs::write(&path, content).with_context(|| format!("无法写入: {}", path.display()))?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// 逐行读取回答的交互提示
+struct Prompt<'a, R, W> {
+    input: &'a mut R,
+    output: &'a mut W,
+}
+
+impl<R: BufRead, W: Write> Prompt<'_, R, W> {
+    /// 提问并读取一行回答，回答为空或输入结束时使用默认值
+    fn ask(&mut self, question: &str, default: &str) -> Result<String> {
+        if default.is_empty() {
+            write!(self.output, "{}: ", question)?;
+        } else {
+            write!(self.output, "{} [{}]: ", question, default)?;
+        }
+        self.output.flush()?;
+
+        let mut answer = String::new();
+        self.input.read_line(&mut answer).context("读取输入失败")?;
+        let answer = answer.trim();
+        Ok(if answer.is_empty() { default } else { answer }.to_string())
+    }
+
+    /// 提问端口号，输入无效时重新提问
+    fn ask_port(&mut self, question: &str, default: u16) -> Result<u16> {
+        loop {
+            match self.ask(question, &default.to_string())?.parse() {
+                Ok(port) if port > 0 => return Ok(port),
+                _ => writeln!(self.output, "请输入 1-65535 之间的端口号")?,
+            }
+        }
+    }
+}
+
+/// 转换为TOML基本字符串
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// 打印合并后的配置
+///
+/// 每行一个配置项，格式为 `key = value  # 来源`，来源为配置文件路径、环境变量或 `default`
+/// （未在任何配置源中设置，使用内置默认值）。名称包含 `password` 的配置项会被隐藏
+///
+/// # param
+/// * `dir` - 配置目录
+/// * `environment` - 环境名称
+/// * `output` - 输出目标
+pub fn show(dir: &Path, environment: &str, output: &mut impl Write) -> Result<()> {
+    let config = loader::build_config(&dir.to_string_lossy(), environment)?;
+    let app_config: AppConfig = config.clone().try_deserialize()?;
+
+    let mut entries = Vec::new();
+    flatten("", &serde_json::to_value(&app_config)?, &mut entries);
+    for (key, value) in entries {
+        let origin = lookup(&config.cache, &key)
+            .and_then(|value| value.origin())
+            .unwrap_or("default");
+        writeln!(output, "{} = {}  # {}", key, value, origin)?;
+    }
+
+    Ok(())
+}
+
+/// 将配置展开为 `(路径, 值)` 列表，路径格式与校验报告一致，如 `stu_config.list[0].email`
+fn flatten(key: &str, value: &JsonValue, entries: &mut Vec<(String, String)>) {
+    match value {
+        JsonValue::Object(map) if !map.is_empty() => {
+            for (name, child) in map {
+                let child_key = if key.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", key, name)
+                };
+                flatten(&child_key, child, entries);
+            }
+        }
+        JsonValue::Array(array) if !array.is_empty() => {
+            for (index, child) in array.iter().enumerate() {
+                flatten(&format!("{}[{}]", key, index), child, entries);
+            }
+        }
+        JsonValue::Object(_) => entries.push((key.to_string(), "{}".into())),
+        JsonValue::String(text) if is_secret(key) && !text.is_empty() => {
+            entries.push((key.to_string(), quote(REDACTED)))
+        }
+        JsonValue::String(text) => entries.push((key.to_string(), quote(text))),
+        JsonValue::Null => entries.push((key.to_string(), "<未设置>".into())),
+        value => entries.push((key.to_string(), value.to_string())),
+    }
+}
+
+/// 是否为需要隐藏的配置项
+fn is_secret(key: &str) -> bool {
+    key.rsplit('.')
+        .next()
+        .is_some_and(|name| name.contains("password"))
+}
+
+/// 校验配置与邮件模板
+///
+/// # param
+/// * `dir` - 配置目录
+/// * `environment` - 环境名称
+///
+/// # return
+/// * `Result<Vec<ConfigValidationError>>` - 配置有效时返回所有警告，否则返回包含所有问题的校验报告
+pub fn validate(dir: &Path, environment: &str) -> Result<Vec<ConfigValidationError>> {
+    let app_config = loader::load_config_from(&dir.to_string_lossy(), environment)?;
+    ReminderTemplate::load(&app_config.template_config, dir)?;
+    ReminderTemplate::load_invalid(&app_config.template_config, dir)?;
+
+    Ok(app_config.warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::loader::ENV_LOCK;
+    use std::io::Cursor;
+    use tempfile::tempdir;
+
+    /// 依次回答 `init` 的所有问题
+    const ANSWERS: &str = "\n\nteacher@example.com\nsecret\"pass\n\n\n\nimap-secret\n/data/out\n";
+
+    #[test]
+    fn should_scaffold_loadable_config() -> Result<()> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Given
+        let tmp_dir = tempdir()?;
+        let dir = tmp_dir.path().join("cfg");
+        let mut output = Vec::new();
+
+        // When
+        let written = init(&dir, false, &mut Cursor::new(ANSWERS), &mut output)?;
+
+        // Then
+        assert_eq!(written.len(), 9);
+        assert_eq!(fs::read_to_string(dir.join(".gitignore"))?, "local.toml\n");
+        assert!(String::from_utf8(output)?.contains("SMTP服务器 [smtp.163.com]: "));
+        let config: AppConfig =
+            loader::build_config(&dir.to_string_lossy(), "dev")?.try_deserialize()?;
+        assert_eq!(config.smtp_config.server, "smtp.163.com");
+        assert_eq!(config.smtp_config.password, "secret\"pass");
+        assert_eq!(config.imap_config.username, "teacher@example.com");
+        assert_eq!(config.imap_config.out_dir, "/data/out");
+        assert_eq!(config.log_config.level, "debug");
+        validate(&dir, "dev")?;
+        let prod = fs::read_to_string(dir.join("prod.toml"))?;
+        assert!(prod.contains("password = \"${SMTP_PASSWORD}\""));
+        assert!(!prod.contains("imap-secret"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_not_overwrite_without_force() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        fs::write(tmp_dir.path().join("local.toml"), "# mine\n")?;
+
+        // When
+        let result = init(
+            tmp_dir.path(),
+            false,
+            &mut Cursor::new(ANSWERS),
+            &mut Vec::new(),
+        );
+
+        // Then
+        assert!(result.unwrap_err().to_string().contains("local.toml"));
+        assert_eq!(
+            fs::read_to_string(tmp_dir.path().join("local.toml"))?,
+            "# mine\n"
+        );
+        init(
+            tmp_dir.path(),
+            true,
+            &mut Cursor::new(ANSWERS),
+            &mut Vec::new(),
+        )?;
+        assert!(fs::read_to_string(tmp_dir.path().join("local.toml"))?.contains("imap-secret"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_show_redacted_values_with_origin() -> Result<()> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Given
+        let tmp_dir = tempdir()?;
+        init(
+            tmp_dir.path(),
+            false,
+            &mut Cursor::new(ANSWERS),
+            &mut Vec::new(),
+        )?;
+        let mut output = Vec::new();
+
+        // When
+        show(tmp_dir.path(), "dev", &mut output)?;
+
+        // Then
+        let output = String::from_utf8(output)?;
+        assert!(!output.contains("secret"));
+        assert!(output.contains("imap_config.password = \"******\"  # "));
+        let line = |key: &str| {
+            output
+                .lines()
+                .find(|line| line.starts_with(&format!("{} = ", key)))
+                .unwrap_or_default()
+                .to_string()
+        };
+        assert!(line("imap_config.out_dir").ends_with("local.toml"));
+        assert!(line("log_config.level").ends_with("dev.toml"));
+        assert!(line("imap_config.mailbox").contains("default.toml"));
+        assert_eq!(line("strict"), "strict = <未设置>  # default");
+        assert!(line("stu_config.list").starts_with("stu_config.list = []  # "));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_invalid_config() -> Result<()> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Given
+        let tmp_dir = tempdir()?;
+        init(
+            tmp_dir.path(),
+            false,
+            &mut Cursor::new(ANSWERS),
+            &mut Vec::new(),
+        )?;
+        fs::write(
+            tmp_dir.path().join("dev.toml"),
+            "[log_config]\nlevel = \"verbose\"\n",
+        )?;
+
+        // When
+        let error = validate(tmp_dir.path(), "dev").unwrap_err().to_string();
+
+        // Then
+        assert!(error.contains("log_config.level"));
+        assert!(error.contains("dev.toml"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+use config::{Config, ConfigError, Map, Source, Value, ValueKind};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::env;
+
+/// `config` 为环境变量配置源中的值记录的来源
+const ENVIRONMENT_ORIGIN: &str = "the environment";
+
+/// 环境变量占位符：`${VAR}` 或 `${VAR:-default}`
+static PLACEHOLDER_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)(?::-([^}]*))?\}").unwrap());
+
+/// 展开环境变量占位符的配置源
+///
+/// 包装任意配置源，将其中所有字符串值（包括嵌套表和数组中的值）里的
+/// `${VAR}` 替换为环境变量的值，`${VAR:-default}` 在变量未设置或为空时使用默认值。
+/// 变量未设置的值保持原样，它可能被后面的配置源覆盖，合并后再由 [`check_unresolved`] 报告。
+/// 展开后的值保留原始来源，便于定位错误
+#[derive(Debug, Clone)]
+pub struct Interpolated<S> {
+    /// 被包装的配置源
+    source: S,
+}
+
+impl<S> Interpolated<S> {
+    /// 包装配置源
+    pub fn new(source: S) -> Self {
+        Self { source }
+    }
+}
+
+impl<S> Source for Interpolated<S>
+where
+    S: Source + Clone + Send + Sync + 'static,
+{
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        let mut map = self.source.collect()?;
+        for value in map.values_mut() {
+            expand_value(value);
+        }
+        Ok(map)
+    }
+}
+
+/// 递归展开配置值中的占位符，变量未设置的字符串保持原样
+fn expand_value(value: &mut Value) {
+    match &mut value.kind {
+        ValueKind::String(text) => {
+            if let Ok(expanded) = expand(text, |name| env::var(name).ok()) {
+                *text = expanded;
+            }
+        }
+        ValueKind::Table(table) => table.values_mut().for_each(expand_value),
+        ValueKind::Array(array) => array.iter_mut().for_each(expand_value),
+        _ => {}
+    }
+}
+
+/// 检查合并后的配置中是否仍有无法展开的占位符
+///
+/// 只报告最终生效的配置项，被后面的配置源（包括 `HSCK_` 环境变量）覆盖的占位符不会报错
+///
+/// # param
+/// * `config` - 合并所有配置源后的配置
+///
+/// # return
+/// * `Result<(), ConfigError>` - 检查通过，或列出每个未设置的变量及其配置项和来源
+pub fn check_unresolved(config: &Config) -> Result<(), ConfigError> {
+    let mut problems = Vec::new();
+    if let ValueKind::Table(table) = &config.cache.kind {
+        for (key, value) in table {
+            collect_unresolved(key, value, &mut problems);
+        }
+    }
+
+    match problems.is_empty() {
+        true => Ok(()),
+        false => Err(ConfigError::Message(problems.join("\n"))),
+    }
+}
+
+/// 递归收集配置值中未设置的环境变量
+fn collect_unresolved(key: &str, value: &Value, problems: &mut Vec<String>) {
+    match &value.kind {
+        ValueKind::String(text) if value.origin() != Some(ENVIRONMENT_ORIGIN) => {
+            if let Err(name) = expand(text, |name| env::var(name).ok()) {
+                problems.push(format!(
+                    "环境变量 {} 未设置（配置项 {}，来源 {}），请设置该变量或使用 ${{{}:-默认值}}",
+                    name,
+                    key,
+                    value.origin().unwrap_or("未知来源"),
+                    name
+                ));
+            }
+        }
+        ValueKind::Table(table) => {
+            for (child_key, child) in table {
+                collect_unresolved(&format!("{}.{}", key, child_key), child, problems);
+            }
+        }
+        ValueKind::Array(array) => {
+            for (index, child) in array.iter().enumerate() {
+                collect_unresolved(&format!("{}[{}]", key, index), child, problems);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 展开字符串中的环境变量占位符
+///
+/// # param
+/// * `text` - 可能包含占位符的字符串
+/// * `lookup` - 查询变量值的函数
+///
+/// # return
+/// * `Result<String, String>` - 展开后的字符串，或第一个未设置且没有默认值的变量名
+pub fn expand(text: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut missing = None;
+    let expanded = PLACEHOLDER_REGEX.replace_all(text, |caps: &Captures| {
+        let name = &caps[1];
+        match (lookup(name), caps.get(2)) {
+            (Some(value), Some(default)) if value.is_empty() => default.as_str().to_string(),
+            (Some(value), _) => value,
+            (None, Some(default)) => default.as_str().to_string(),
+            (None, None) => {
+                missing.get_or_insert_with(|| name.to_string());
+                String::new()
+            }
+        }
+    });
+
+    match missing {
+        Some(name) => Err(name),
+        None => Ok(expanded.into_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::{Config, File, FileFormat};
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "SMTP_USERNAME" => Some("teacher@example.com".into()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn should_expand_placeholders() {
+        assert_eq!(
+            expand("${SMTP_USERNAME}", lookup).as_deref(),
+            Ok("teacher@example.com")
+        );
+        assert_eq!(
+            expand("user=${SMTP_USERNAME};port=${SMTP_PORT:-465}", lookup).as_deref(),
+            Ok("user=teacher@example.com;port=465")
+        );
+        assert_eq!(
+            expand("${EMPTY:-fallback}", lookup).as_deref(),
+            Ok("fallback")
+        );
+        assert_eq!(expand("${EMPTY}", lookup).as_deref(), Ok(""));
+        assert_eq!(expand("$HOME {x}", lookup).as_deref(), Ok("$HOME {x}"));
+    }
+
+    #[test]
+    fn should_report_missing_variable() {
+        assert_eq!(
+            expand("${SMTP_USERNAME}:${SMTP_PASSWORD}", lookup),
+            Err("SMTP_PASSWORD".to_string())
+        );
+    }
+
+    #[test]
+    fn should_expand_nested_values_and_name_missing_key() {
+        // Given
+        env::set_var("INTERPOLATION_TEST_NAME", "测试学生");
+        let toml = r#"
+[stu_config]
+list = [{ name = "${INTERPOLATION_TEST_NAME}", email = "${INTERPOLATION_TEST_EMAIL:-a@b.com}" }]
+
+[smtp_config]
+password = "${INTERPOLATION_TEST_MISSING}"
+"#;
+        let source = |toml: &str| Interpolated::new(File::from_str(toml, FileFormat::Toml));
+
+        // When
+        let missing = Config::builder()
+            .add_source(source(toml))
+            .build()
+            .and_then(|config| check_unresolved(&config));
+        let config = Config::builder()
+            .add_source(source(toml.split("[smtp_config]").next().unwrap()))
+            .build()
+            .unwrap();
+
+        // Then
+        let message = missing.unwrap_err().to_string();
+        assert!(message.contains("INTERPOLATION_TEST_MISSING"));
+        assert!(message.contains("smtp_config.password"));
+        assert_eq!(
+            config.get_string("stu_config.list[0].name").unwrap(),
+            "测试学生"
+        );
+        assert_eq!(
+            config.get_string("stu_config.list[0].email").unwrap(),
+            "a@b.com"
+        );
+    }
+}
@@ -1,8 +1,18 @@
-use config::{Config, ConfigError, File};
+use config::{Config, ConfigError, Environment, File};
 use log::info;
 use std::env;
+use std::path::Path;
 
 use super::app_config::AppConfig;
+use super::interpolation::{check_unresolved, Interpolated};
+use super::validation::ConfigValidationError;
+
+/// 覆盖配置项的环境变量前缀，如 `HSCK_SMTP_CONFIG__PASSWORD` 覆盖 `smtp_config.password`
+pub const ENV_PREFIX: &str = "HSCK";
+
+/// 加载配置的测试会修改进程级环境变量，需要串行执行
+#[cfg(test)]
+pub(crate) static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
 
 /// 基于当前环境加载配置
 ///
@@ -10,42 +20,104 @@ use super::app_config::AppConfig;
 /// 1. default.toml - 基础配置
 /// 2. {environment}.toml - 环境特定配置（dev, prod等）
 /// 3. local.toml - 本地覆盖（不在版本控制中）
+/// 4. `HSCK_` 前缀的环境变量 - 以 `__` 分隔层级，如 `HSCK_SMTP_CONFIG__PASSWORD`
+///
+/// 配置文件中的字符串值支持 `${VAR}` 与 `${VAR:-default}` 环境变量占位符，
+/// 变量未设置时只有在该配置项没有被后面的源覆盖时才报错
 pub fn load_config() -> Result<AppConfig, ConfigError> {
-    // 确定环境，dev/prod
+    load_config_from(&config_dir(), &current_environment())
+}
+
+/// 从指定配置目录加载并校验指定环境的配置
+///
+/// 除合并所有配置源外，还会读取 `stu_config.files` 中的学生名单文件
+///
+/// # param
+/// * `cfg_dir` - 配置目录
+/// * `environment` - 环境名称
+///
+/// # return
+/// * `Result<AppConfig, ConfigError>` - 校验通过的配置或错误
+pub fn load_config_from(cfg_dir: &str, environment: &str) -> Result<AppConfig, ConfigError> {
+    let config = build_config(cfg_dir, environment)?;
+    let mut app_config: AppConfig = config.clone().try_deserialize()?;
+
+    // 读取学生名单文件
+    app_config
+        .stu_config
+        .load_files(Path::new(cfg_dir))
+        .map_err(|e| ConfigError::Message(format!("{:#}", e)))?;
+
+    // 校验配置
+    app_config.warnings = validate_config(&app_config, environment, &config)?;
+
+    Ok(app_config)
+}
+
+/// 当前环境，dev/prod
+pub fn current_environment() -> String {
     let environment = env::var("APP_ENV").unwrap_or_else(|_| {
         info!("The 'APP_ENV' is not set. It defaults to 'dev'.");
         "dev".into()
     });
 
     info!("Loading environment configuration: {}", environment);
+    environment
+}
 
-    // 定义配置目录
-    let cfg_dir = env::var("CONFIG_DIR").unwrap_or_else(|_| "cfg".into());
+/// 配置目录
+pub fn config_dir() -> String {
+    env::var("CONFIG_DIR").unwrap_or_else(|_| "cfg".into())
+}
 
-    // 构建配置
-    let app_config: AppConfig = Config::builder()
+/// 合并所有配置源，得到未校验的原始配置
+///
+/// 原始配置中的每个值都保留了来源（配置文件路径或环境变量），
+/// 可用于定位错误或展示配置项来自哪一层
+///
+/// # param
+/// * `cfg_dir` - 配置目录
+/// * `environment` - 环境名称
+///
+/// # return
+/// * `Result<Config, ConfigError>` - 合并后的原始配置或错误
+pub fn build_config(cfg_dir: &str, environment: &str) -> Result<Config, ConfigError> {
+    let config = Config::builder()
         // 添加默认配置
-        .add_source(File::with_name(&format!("{}/default", cfg_dir)))
+        .add_source(Interpolated::new(File::with_name(&format!(
+            "{}/default",
+            cfg_dir
+        ))))
         // 添加环境特定配置
-        .add_source(File::with_name(&format!("{}/{}", cfg_dir, environment)).required(false))
+        .add_source(Interpolated::new(
+            File::with_name(&format!("{}/{}", cfg_dir, environment)).required(false),
+        ))
         // 添加本地配置覆盖（不在版本控制中）
-        .add_source(File::with_name(&format!("{}/local", cfg_dir)).required(false))
-        .build()?
-        .try_deserialize()?;
-
-    // 校验配置
-    validate_config(&app_config)?;
-
-    Ok(app_config)
+        .add_source(Interpolated::new(
+            File::with_name(&format!("{}/local", cfg_dir)).required(false),
+        ))
+        // 添加环境变量覆盖
+        .add_source(
+            Environment::with_prefix(ENV_PREFIX)
+                .prefix_separator("_")
+                .separator("__"),
+        )
+        .build()?;
+
+    // 占位符可能被后面的配置源覆盖，合并后再检查
+    check_unresolved(&config)?;
+    Ok(config)
 }
 
-/// 校验配置
-fn validate_config(config: &AppConfig) -> Result<(), ConfigError> {
-    if let Err(e) = config.validate() {
-        return Err(ConfigError::Message(e));
-    }
-
-    Ok(())
+/// 校验配置，所有问题合并为一份报告，并标明每个配置项的来源文件
+fn validate_config(
+    app_config: &AppConfig,
+    environment: &str,
+    config: &Config,
+) -> Result<Vec<ConfigValidationError>, ConfigError> {
+    app_config
+        .validate(environment, Some(config))
+        .map_err(|report| ConfigError::Foreign(Box::new(report)))
 }
 
 #[cfg(test)]
@@ -59,6 +131,8 @@ mod tests {
 
     #[test]
     fn test_load_dev_config() -> Result<(), Box<dyn std::error::Error>> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
         // Given
         let tmp_dir = tempdir()?;
         create_test_config_files(tmp_dir.path())?;
@@ -84,6 +158,8 @@ mod tests {
 
     #[test]
     fn test_load_prod_config() -> Result<(), Box<dyn std::error::Error>> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
         // Given
         let tmp_dir = tempdir()?;
         create_test_config_files(tmp_dir.path())?;
@@ -99,13 +175,349 @@ mod tests {
         assert_eq!(config.smtp_config.port, 465);
         assert_eq!(config.imap_config.server, "imap.test.com");
         assert_eq!(config.imap_config.out_dir, "/var/data/prod/out");
-        assert_eq!(config.stu_config.list.len(), 0);
+        assert_eq!(config.stu_config.list.len(), 1);
         assert!(!config.log_config.console_output);
         assert_eq!(config.log_config.level, "info");
 
         Ok(())
     }
 
+    #[test]
+    fn test_expand_placeholders_and_env_overrides() -> Result<(), Box<dyn std::error::Error>> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Given
+        let tmp_dir = tempdir()?;
+        create_test_config_files(tmp_dir.path())?;
+        let cfg_dir = tmp_dir.path().join("cfg");
+        fs::write(
+            cfg_dir.join("local.toml"),
+            r#"
+[imap_config]
+username = "${LOADER_TEST_IMAP_USERNAME}"
+out_dir = "${LOADER_TEST_OUT_DIR:-/default/out}"
+"#,
+        )?;
+
+        env::set_var("APP_ENV", "prod");
+        env::set_var("CONFIG_DIR", cfg_dir.to_str().unwrap());
+        env::set_var("LOADER_TEST_IMAP_USERNAME", "ta@example.com");
+        env::set_var("HSCK_SMTP_CONFIG__PASSWORD", "from_env");
+        env::set_var("HSCK_SMTP_CONFIG__PORT", "2465");
+
+        // When
+        let config = load_config();
+        env::remove_var("HSCK_SMTP_CONFIG__PASSWORD");
+        env::remove_var("HSCK_SMTP_CONFIG__PORT");
+        env::remove_var("LOADER_TEST_IMAP_USERNAME");
+        let config = config?;
+
+        // Then
+        assert_eq!(config.imap_config.username, "ta@example.com");
+        assert_eq!(config.imap_config.out_dir, "/default/out");
+        assert_eq!(config.smtp_config.password, "from_env");
+        assert_eq!(config.smtp_config.port, 2465);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_missing_env_variable() -> Result<(), Box<dyn std::error::Error>> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Given
+        let tmp_dir = tempdir()?;
+        create_test_config_files(tmp_dir.path())?;
+        let cfg_dir = tmp_dir.path().join("cfg");
+        fs::write(
+            cfg_dir.join("local.toml"),
+            "[smtp_config]\npassword = \"${LOADER_TEST_UNSET_PASSWORD}\"\n",
+        )?;
+
+        env::set_var("APP_ENV", "dev");
+        env::set_var("CONFIG_DIR", cfg_dir.to_str().unwrap());
+
+        // When
+        let error = load_config().unwrap_err().to_string();
+
+        // Then
+        assert!(error.contains("LOADER_TEST_UNSET_PASSWORD"));
+        assert!(error.contains("smtp_config.password"));
+        assert!(error.contains("local.toml"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_env_override_replaces_unset_placeholder() -> Result<(), Box<dyn std::error::Error>> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Given
+        let tmp_dir = tempdir()?;
+        create_test_config_files(tmp_dir.path())?;
+        let cfg_dir = tmp_dir.path().join("cfg");
+        fs::write(
+            cfg_dir.join("local.toml"),
+            r#"
+[imap_config]
+password = "${LOADER_TEST_UNSET_IMAP_PASSWORD}"
+username = "${LOADER_TEST_UNSET_IMAP_USERNAME}"
+"#,
+        )?;
+
+        env::set_var("APP_ENV", "prod");
+        env::set_var("CONFIG_DIR", cfg_dir.to_str().unwrap());
+        env::set_var("HSCK_IMAP_CONFIG__PASSWORD", "from_env");
+
+        // When
+        let error = load_config().unwrap_err().to_string();
+        env::set_var("HSCK_IMAP_CONFIG__USERNAME", "ta@example.com");
+        let config = load_config();
+        env::remove_var("HSCK_IMAP_CONFIG__PASSWORD");
+        env::remove_var("HSCK_IMAP_CONFIG__USERNAME");
+        let config = config?;
+
+        // Then
+        assert!(
+            error.contains("LOADER_TEST_UNSET_IMAP_USERNAME"),
+            "{}",
+            error
+        );
+        assert!(
+            !error.contains("LOADER_TEST_UNSET_IMAP_PASSWORD"),
+            "{}",
+            error
+        );
+        assert_eq!(config.imap_config.password, "from_env");
+        assert_eq!(config.imap_config.username, "ta@example.com");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_all_problems_in_strict_mode() -> Result<(), Box<dyn std::error::Error>> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Given
+        let tmp_dir = tempdir()?;
+        create_test_config_files(tmp_dir.path())?;
+        let cfg_dir = tmp_dir.path().join("cfg");
+        fs::write(
+            cfg_dir.join("local.toml"),
+            r#"
+strict = true
+
+[smtp_config]
+password = ""
+
+[stu_config]
+list = []
+
+[log_config]
+level = "verbose"
+"#,
+        )?;
+
+        env::set_var("APP_ENV", "dev");
+        env::set_var("CONFIG_DIR", cfg_dir.to_str().unwrap());
+
+        // When
+        let error = load_config().unwrap_err().to_string();
+
+        // Then
+        assert!(error.contains("共 3 个问题"));
+        assert!(error.contains("smtp_config.password ("));
+        assert!(error.contains("local.toml): is required in strict mode"));
+        assert!(error.contains("stu_config.list ("));
+        assert!(error.contains("log_config.level ("));
+        assert!(error.contains("invalid value `verbose`"));
+        assert!(error.contains("valid values are: error, warn, info, debug, trace"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_skip_strict_checks_when_disabled() -> Result<(), Box<dyn std::error::Error>> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Given
+        let tmp_dir = tempdir()?;
+        create_test_config_files(tmp_dir.path())?;
+        let cfg_dir = tmp_dir.path().join("cfg");
+        fs::write(
+            cfg_dir.join("local.toml"),
+            "strict = false\n\n[stu_config]\nlist = []\n",
+        )?;
+
+        env::set_var("APP_ENV", "prod");
+        env::set_var("CONFIG_DIR", cfg_dir.to_str().unwrap());
+
+        // When
+        let config = load_config()?;
+
+        // Then
+        assert!(config.stu_config.list.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_roster_files() -> Result<(), Box<dyn std::error::Error>> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Given
+        let tmp_dir = tempdir()?;
+        create_test_config_files(tmp_dir.path())?;
+        let cfg_dir = tmp_dir.path().join("cfg");
+        fs::write(
+            cfg_dir.join("local.toml"),
+            "[[stu_config.files]]\npath = \"roster.csv\"\n",
+        )?;
+        fs::write(
+            cfg_dir.join("roster.csv"),
+            "学号,姓名,邮箱\n2021003,测试学生3,test3@example.com\n",
+        )?;
+
+        env::set_var("APP_ENV", "dev");
+        env::set_var("CONFIG_DIR", cfg_dir.to_str().unwrap());
+
+        // When
+        let config = load_config()?;
+        fs::write(
+            cfg_dir.join("roster.csv"),
+            "学号,姓名,邮箱\n2021003,测试学生3,test3@example.com\n2021001,测试学生1,TEST1@example.com\n",
+        )?;
+        let lenient = load_config()?;
+        fs::write(
+            cfg_dir.join("local.toml"),
+            "strict = true\n\n[[stu_config.files]]\npath = \"roster.csv\"\n",
+        )?;
+        let error = load_config().unwrap_err().to_string();
+
+        // Then
+        let names: Vec<&str> = config
+            .stu_config
+            .list
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, ["测试学生1", "测试学生2", "测试学生3"]);
+        assert!(config.warnings.is_empty());
+        let [warning] = lenient.warnings.as_slice() else {
+            panic!("unexpected warnings: {:?}", lenient.warnings);
+        };
+        let warning = warning.to_string();
+        assert!(warning.contains("roster.csv:3: duplicate student email `TEST1@example.com`"));
+        assert!(warning.contains("first defined at stu_config.list[0] ("));
+        assert!(warning.ends_with("dev.toml)"));
+        assert!(error.contains("共 1 个问题"));
+        assert!(error.contains("roster.csv:3: duplicate student email `TEST1@example.com`"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_flag_ambiguous_roster_entries() -> Result<(), Box<dyn std::error::Error>> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Given
+        let tmp_dir = tempdir()?;
+        create_test_config_files(tmp_dir.path())?;
+        let cfg_dir = tmp_dir.path().join("cfg");
+        fs::write(
+            cfg_dir.join("local.toml"),
+            r#"
+[stu_config]
+list = [
+    { id = "2021001", name = "王伟", email = "wangwei@example.com" },
+    { id = "2021001", name = "王伟明", email = "wangweiming@example.com" },
+    { name = "李四 ", email = "lisi@example.com" }
+]
+"#,
+        )?;
+
+        env::set_var("APP_ENV", "dev");
+        env::set_var("CONFIG_DIR", cfg_dir.to_str().unwrap());
+
+        // When
+        let config = load_config()?;
+
+        // Then
+        let warnings: Vec<String> = config.warnings.iter().map(ToString::to_string).collect();
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert!(warnings[0].starts_with("stu_config.list[2].name ("));
+        assert!(warnings[0].ends_with("leading or trailing whitespace in `李四 `"));
+        assert!(warnings[1].starts_with("stu_config.list[1] ("));
+        assert!(warnings[1].contains("duplicate student id `2021001`"));
+        assert!(warnings[2].starts_with("stu_config.list[0] ("));
+        assert!(warnings[2].contains("name `王伟` is part of `王伟明` at stu_config.list[1] ("));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_invalid_check_config_and_homework() -> Result<(), Box<dyn std::error::Error>> {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Given
+        let tmp_dir = tempdir()?;
+        create_test_config_files(tmp_dir.path())?;
+        let cfg_dir = tmp_dir.path().join("cfg");
+        fs::write(
+            cfg_dir.join("local.toml"),
+            r#"
+[check_config]
+exclude = ["[abc"]
+
+[[homework]]
+id = "hw3"
+deadline = "2024-10-01 23:59"
+max_size = "20 MiB"
+
+[[homework]]
+id = "hw4"
+deadline = "下周一"
+filename_pattern = "{id}_{nmae}.pdf"
+required_files = ["report.pdf", "src/[main"]
+
+[[homework]]
+id = "hw3"
+kind = "group"
+"#,
+        )?;
+
+        env::set_var("APP_ENV", "dev");
+        env::set_var("CONFIG_DIR", cfg_dir.to_str().unwrap());
+
+        // When
+        let error = load_config().unwrap_err().to_string();
+
+        // Then
+        assert!(error.contains("共 7 个问题"), "{}", error);
+        assert!(
+            error.contains("homework[1].required_files[1] ("),
+            "{}",
+            error
+        );
+        assert!(error.contains("check_config.exclude[0] ("), "{}", error);
+        assert!(error.contains("invalid size `20 MiB`"), "{}", error);
+        assert!(error.contains("homework[1].deadline ("), "{}", error);
+        assert!(error.contains("invalid deadline `下周一`"), "{}", error);
+        assert!(
+            error.contains("homework[1].filename_pattern ("),
+            "{}",
+            error
+        );
+        assert!(error.contains("homework[2].groups (default)"), "{}", error);
+        assert!(
+            error.contains("duplicate homework id `hw3`, first defined at homework[0]"),
+            "{}",
+            error
+        );
+
+        Ok(())
+    }
+
     /// 创建测试配置文件
     fn create_test_config_files(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
         // 创建配置目录
@@ -168,6 +580,9 @@ encryption = "tls"
 [imap_config]
 out_dir = "/var/data/prod/out"
 
+[stu_config]
+list = [{ name = "生产学生", email = "prod@example.com" }]
+
 [log_config]
 level = "info"
 console_output = false
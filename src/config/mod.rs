@@ -1,3 +1,7 @@
 pub mod app_config;
+pub mod command;
+pub mod interpolation;
 pub mod loader;
+pub mod roster;
 pub mod stu;
+pub mod validation;
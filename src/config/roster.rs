@@ -0,0 +1,416 @@
+use anyhow::{anyhow, bail, Context, Result};
+use calamine::{open_workbook_auto, Reader};
+use encoding_rs::GB18030;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use super::stu::{Stu, EMAIL_REGEX};
+use super::validation::ConfigValidationError;
+
+/// 学生名单文件配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RosterFile {
+    /// 文件路径，相对路径相对于配置目录，按扩展名识别 CSV 或 Excel（xlsx/xls/ods）格式
+    pub path: String,
+
+    /// Excel工作表名称，未设置时使用第一个工作表
+    #[serde(default)]
+    pub sheet: Option<String>,
+
+    /// 表头列名映射
+    #[serde(default)]
+    pub columns: RosterColumns,
+}
+
+/// 名单文件的表头列名
+///
+/// 姓名与邮箱列是必需的，其余列在表头中不存在时忽略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RosterColumns {
+    #[serde(default = "default_name_column")]
+    pub name: String,
+
+    #[serde(default = "default_email_column")]
+    pub email: String,
+
+    #[serde(default = "default_id_column")]
+    pub id: String,
+
+    #[serde(default = "default_class_column")]
+    pub class: String,
+
+    /// 小组列，单元格中的第一个数字作为组号，如 `第3组`
+    #[serde(default = "default_group_column")]
+    pub group: String,
+
+    /// 标签列，多个标签以逗号、分号、顿号或空白分隔
+    #[serde(default = "default_tags_column")]
+    pub tags: String,
+}
+
+impl Default for RosterColumns {
+    fn default() -> Self {
+        Self {
+            name: default_name_column(),
+            email: default_email_column(),
+            id: default_id_column(),
+            class: default_class_column(),
+            group: default_group_column(),
+            tags: default_tags_column(),
+        }
+    }
+}
+
+fn default_name_column() -> String {
+    "姓名".into()
+}
+
+fn default_email_column() -> String {
+    "邮箱".into()
+}
+
+fn default_id_column() -> String {
+    "学号".into()
+}
+
+fn default_class_column() -> String {
+    "班级".into()
+}
+
+fn default_group_column() -> String {
+    "小组".into()
+}
+
+fn default_tags_column() -> String {
+    "标签".into()
+}
+
+/// 单元格中的组号
+static GROUP_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\d+").unwrap());
+
+/// 名单文件中的一名学生
+#[derive(Debug, Clone)]
+pub struct RosterRow {
+    /// 所在行号，从1开始
+    pub line: usize,
+
+    pub student: Stu,
+}
+
+/// 名单文件读取结果
+#[derive(Debug, Default)]
+pub struct Roster {
+    /// 格式正确的学生
+    pub rows: Vec<RosterRow>,
+
+    /// 格式错误的行，不会加入名单
+    pub problems: Vec<ConfigValidationError>,
+}
+
+/// 读取学生名单文件
+///
+/// 第一个非空行作为表头，空行会被忽略。缺少姓名或邮箱、邮箱格式错误的行记录为问题，
+/// 不会中断读取。CSV文件可以是UTF-8（可带BOM）或GBK编码
+///
+/// # param
+/// * `file` - 名单文件配置
+/// * `path` - 名单文件路径（已相对于配置目录解析）
+///
+/// # return
+/// * `Result<Roster>` - 读取结果，文件无法读取或缺少必需的列时返回错误
+pub fn read(file: &RosterFile, path: &Path) -> Result<Roster> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase);
+    let records = match extension.as_deref() {
+        Some("csv") => read_csv(path)?,
+        Some("xlsx" | "xlsm" | "xls" | "ods") => read_sheet(path, file.sheet.as_deref())?,
+        _ => bail!("不支持的名单文件格式: {}", path.display()),
+    };
+
+    let mut records = records
+        .into_iter()
+        .filter(|(_, cells)| cells.iter().any(|cell| !cell.is_empty()));
+    let Some((_, header)) = records.next() else {
+        return Ok(Roster::default());
+    };
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|cell| cell.eq_ignore_ascii_case(name))
+    };
+    let required = |name: &str| {
+        column(name).ok_or_else(|| {
+            anyhow!(
+                "名单文件 {} 缺少 `{}` 列，表头为: {}",
+                path.display(),
+                name,
+                header.join(", ")
+            )
+        })
+    };
+    let columns = &file.columns;
+    let name_column = required(&columns.name)?;
+    let email_column = required(&columns.email)?;
+    let id_column = column(&columns.id);
+    let class_column = column(&columns.class);
+    let group_column = column(&columns.group);
+    let tags_column = column(&columns.tags);
+
+    let mut roster = Roster::default();
+    for (line, cells) in records {
+        let cell = |index: Option<usize>| {
+            index
+                .and_then(|index| cells.get(index))
+                .map_or("", String::as_str)
+        };
+        let optional =
+            |index: Option<usize>| Some(cell(index).to_string()).filter(|v| !v.is_empty());
+        let (name, email) = (cell(Some(name_column)), cell(Some(email_column)));
+        let group = cell(group_column);
+        let group_number = GROUP_REGEX
+            .find(group)
+            .and_then(|number| number.as_str().parse().ok());
+
+        let reason = if name.is_empty() {
+            Some(format!("missing `{}`", columns.name))
+        } else if email.is_empty() {
+            Some(format!("missing `{}`", columns.email))
+        } else if !EMAIL_REGEX.is_match(email) {
+            Some(format!("invalid email address `{}`", email))
+        } else if !group.is_empty() && group_number.is_none() {
+            Some(format!("invalid group `{}`", group))
+        } else {
+            None
+        };
+
+        match reason {
+            Some(reason) => roster.problems.push(ConfigValidationError::MalformedRow {
+                file: path.display().to_string(),
+                line,
+                reason,
+            }),
+            None => roster.rows.push(RosterRow {
+                line,
+                student: Stu {
+                    id: optional(id_column),
+                    name: name.to_string(),
+                    email: email.to_string(),
+                    class: optional(class_column),
+                    group: group_number,
+                    tags: cell(tags_column)
+                        .split([',', '，', ';', '；', '、', ' '])
+                        .filter(|tag| !tag.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                    aliases: vec![],
+                },
+            }),
+        }
+    }
+
+    Ok(roster)
+}
+
+/// 读取CSV文件的所有行及其行号
+fn read_csv(path: &Path) -> Result<Vec<(usize, Vec<String>)>> {
+    let bytes = fs::read(path).with_context(|| format!("无法读取名单文件: {}", path.display()))?;
+    let text = match std::str::from_utf8(&bytes) {
+        Ok(text) => text.trim_start_matches('\u{feff}').to_string(),
+        Err(_) => GB18030.decode(&bytes).0.into_owned(),
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let mut records = Vec::new();
+    for record in reader.records() {
+        let record = record.with_context(|| format!("无法解析名单文件: {}", path.display()))?;
+        // 记录的起始位置可能落在被跳过的空行上，行号从第一个非空字符计算
+        let start = record
+            .position()
+            .map_or(0, |position| position.byte() as usize);
+        let start =
+            start + text[start..].len() - text[start..].trim_start_matches(['\r', '\n']).len();
+        let line = text[..start].matches('\n').count() + 1;
+        records.push((
+            line,
+            record.iter().map(|cell| cell.trim().to_string()).collect(),
+        ));
+    }
+
+    Ok(records)
+}
+
+/// 读取Excel工作表的所有行及其行号
+fn read_sheet(path: &Path, sheet: Option<&str>) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut workbook = open_workbook_auto(path)
+        .with_context(|| format!("无法打开名单文件: {}", path.display()))?;
+    let range = match sheet {
+        Some(sheet) => workbook.worksheet_range(sheet),
+        None => workbook
+            .worksheet_range_at(0)
+            .ok_or_else(|| anyhow!("名单文件 {} 中没有工作表", path.display()))?,
+    }
+    .with_context(|| format!("无法读取名单文件: {}", path.display()))?;
+
+    let first_row = range.start().map_or(0, |(row, _)| row as usize);
+    let records = range
+        .rows()
+        .enumerate()
+        .map(|(index, row)| {
+            let cells = row.iter().map(|cell| cell.to_string().trim().to_string());
+            (first_row + index + 1, cells.collect())
+        })
+        .collect();
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    fn roster_file(path: &str) -> RosterFile {
+        RosterFile {
+            path: path.into(),
+            sheet: None,
+            columns: RosterColumns::default(),
+        }
+    }
+
+    #[test]
+    fn should_read_csv_and_report_malformed_rows() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let path = tmp_dir.path().join("roster.csv");
+        let (gbk, _, _) = GB18030.encode(
+            "学号,姓名,邮箱,班级\n\
+             2021001,张三,zhangsan@example.com,1班\n\
+             \n\
+             2021002,李四,,1班\n\
+             2021003,王五,not-an-email,2班\n\
+             2021004, 赵六 ,zhaoliu@example.com\n",
+        );
+        fs::write(&path, gbk)?;
+
+        // When
+        let roster = read(&roster_file("roster.csv"), &path)?;
+
+        // Then
+        let students: Vec<(usize, &str)> = roster
+            .rows
+            .iter()
+            .map(|row| (row.line, row.student.name.as_str()))
+            .collect();
+        assert_eq!(students, [(2, "张三"), (6, "赵六")]);
+        assert_eq!(roster.rows[0].student.id.as_deref(), Some("2021001"));
+        assert_eq!(roster.rows[0].student.class.as_deref(), Some("1班"));
+        assert_eq!(roster.rows[1].student.class, None);
+        let problems: Vec<String> = roster.problems.iter().map(ToString::to_string).collect();
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].ends_with("roster.csv:4: missing `邮箱`"));
+        assert!(problems[1].ends_with("roster.csv:5: invalid email address `not-an-email`"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_xlsx_with_column_mapping() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let path = tmp_dir.path().join("roster.xlsx");
+        write_xlsx(
+            &path,
+            &[
+                &["Name", "E-mail", "Group", "Tags"],
+                &["张三", "zhangsan@example.com", "第3组", "重修、旁听"],
+                &["李四", "lisi@example.com", "", ""],
+            ],
+        )?;
+        let mut file = roster_file("roster.xlsx");
+        file.columns = RosterColumns {
+            name: "name".into(),
+            email: "e-mail".into(),
+            group: "Group".into(),
+            tags: "Tags".into(),
+            ..Default::default()
+        };
+
+        // When
+        let roster = read(&file, &path)?;
+        let missing_column = read(&roster_file("roster.xlsx"), &path);
+
+        // Then
+        assert!(roster.problems.is_empty());
+        assert_eq!(roster.rows.len(), 2);
+        assert_eq!(roster.rows[1].line, 3);
+        assert_eq!(roster.rows[1].student.email, "lisi@example.com");
+        assert_eq!(roster.rows[0].student.group, Some(3));
+        assert_eq!(roster.rows[0].student.tags, ["重修", "旁听"]);
+        assert_eq!(roster.rows[1].student.group, None);
+        assert!(missing_column
+            .unwrap_err()
+            .to_string()
+            .contains("缺少 `姓名` 列"));
+
+        Ok(())
+    }
+
+    /// 生成只包含一个工作表的最小xlsx文件
+    fn write_xlsx(path: &Path, rows: &[&[&str]]) -> Result<()> {
+        let sheet_data: String = rows
+            .iter()
+            .enumerate()
+            .map(|(index, cells)| {
+                let cells: String = cells
+                    .iter()
+                    .map(|cell| format!(r#"<c t="inlineStr"><is><t>{}</t></is></c>"#, cell))
+                    .collect();
+                format!(r#"<row r="{}">{}</row>"#, index + 1, cells)
+            })
+            .collect();
+        let files = [
+            (
+                "[Content_Types].xml",
+                r#"<?xml version="1.0" encoding="UTF-8"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/></Types>"#.to_string(),
+            ),
+            (
+                "_rels/.rels",
+                r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#.to_string(),
+            ),
+            (
+                "xl/workbook.xml",
+                r#"<?xml version="1.0" encoding="UTF-8"?><workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="名单" sheetId="1" r:id="rId1"/></sheets></workbook>"#.to_string(),
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#.to_string(),
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                format!(
+                    r#"<?xml version="1.0" encoding="UTF-8"?><worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>{}</sheetData></worksheet>"#,
+                    sheet_data
+                ),
+            ),
+        ];
+
+        let mut zip = zip::ZipWriter::new(fs::File::create(path)?);
+        for (name, content) in files {
+            zip.start_file(name, SimpleFileOptions::default())?;
+            zip.write_all(content.as_bytes())?;
+        }
+        zip.finish()?;
+
+        Ok(())
+    }
+}
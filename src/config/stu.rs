@@ -1,25 +1,125 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| {
+use super::validation::{ConfigValidationError, Validation};
+
+pub(crate) static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^[a-zA-Z0-9.!#$%&'*+/=?^_`{|}~-]+@[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?(?:\.[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?)*$").unwrap()
 });
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Stu {
+    /// 学号，填写后优先按学号匹配提交的文件，可以区分同名学生
+    #[serde(default)]
+    pub id: Option<String>,
+
     pub name: String,
     pub email: String,
+
+    /// 班级
+    #[serde(default)]
+    pub class: Option<String>,
+
+    /// 小组编号
+    #[serde(default)]
+    pub group: Option<u32>,
+
+    /// 自定义标签，如 `重修`、`旁听`
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// 备用邮箱，学生可能使用多个邮箱提交作业
+    #[serde(default)]
+    pub aliases: Vec<String>,
 }
 
 impl Stu {
     /// 验证邮箱格式
     ///
-    /// 如果邮箱有效则返回Ok，否则返回错误信息
-    pub fn check_email(&self) -> Result<(), String> {
-        if !EMAIL_REGEX.is_match(&self.email) {
-            return Err(format!("Invalid email format for student: {}", self.name));
+    /// 主邮箱与每个备用邮箱的格式错误都会被记录
+    ///
+    /// # param
+    /// * `validation` - 校验结果收集器
+    /// * `key` - 该学生在配置中的路径，如 `stu_config.list[0]`
+    pub fn check_email(&self, validation: &mut Validation, key: &str) {
+        validation.ensure(
+            EMAIL_REGEX.is_match(&self.email),
+            &format!("{}.email", key),
+            |location| ConfigValidationError::InvalidEmail {
+                location,
+                value: self.email.clone(),
+            },
+        );
+
+        for (index, alias) in self.aliases.iter().enumerate() {
+            validation.ensure(
+                EMAIL_REGEX.is_match(alias),
+                &format!("{}.aliases[{}]", key, index),
+                |location| ConfigValidationError::InvalidEmail {
+                    location,
+                    value: alias.clone(),
+                },
+            );
+        }
+    }
+
+    /// 学生标识，有学号时为 `学号_姓名`，否则为姓名
+    ///
+    /// 用作接收作业时的学生目录名，同名学生不会混在同一个目录中
+    pub fn label(&self) -> String {
+        match &self.id {
+            Some(id) => format!("{}_{}", id, self.name),
+            None => self.name.clone(),
         }
-        Ok(())
+    }
+
+    /// 判断文本中是否包含该学生的学号
+    ///
+    /// 学号前后不能紧邻其他数字，避免 `2021001` 匹配到 `20210012`
+    pub fn id_in(&self, text: &str) -> bool {
+        let Some(id) = self.id.as_deref().filter(|id| !id.is_empty()) else {
+            return false;
+        };
+
+        text.match_indices(id).any(|(start, _)| {
+            let before = text[..start].chars().next_back();
+            let after = text[start + id.len()..].chars().next();
+            !before.is_some_and(|c| c.is_ascii_digit())
+                && !after.is_some_and(|c| c.is_ascii_digit())
+        })
+    }
+
+    /// 判断邮箱地址是否属于该学生（忽略大小写，包括备用邮箱）
+    pub fn owns_address(&self, address: &str) -> bool {
+        let address = address.trim();
+        self.email.eq_ignore_ascii_case(address)
+            || self
+                .aliases
+                .iter()
+                .any(|alias| alias.eq_ignore_ascii_case(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_id_on_digit_boundaries() {
+        // Given
+        let student = Stu {
+            id: Some("2021001".into()),
+            name: "张三".into(),
+            ..Default::default()
+        };
+
+        // Then
+        assert!(student.id_in("2021001_张三_作业3.pdf"));
+        assert!(student.id_in("作业3-2021001.docx"));
+        assert!(!student.id_in("20210012_李四.pdf"));
+        assert!(!student.id_in("12021001.pdf"));
+        assert!(!Stu::default().id_in("2021001.pdf"));
+        assert_eq!(student.label(), "2021001_张三");
     }
 }
@@ -0,0 +1,439 @@
+use config::{Config, Value, ValueKind};
+use std::fmt;
+use thiserror::Error;
+
+use super::loader::ENV_PREFIX;
+
+/// 严格校验的环境
+pub const STRICT_ENVIRONMENTS: [&str; 1] = ["prod"];
+
+/// 配置项位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// 配置项路径，如 `smtp_config.password`、`stu_config.list[2].email`
+    pub key: String,
+
+    /// 配置项来源文件，未在任何配置源中设置（使用默认值）时为None
+    pub file: Option<String>,
+}
+
+impl Location {
+    /// 覆盖该配置项的环境变量名，如 `HSCK_SMTP_CONFIG__PASSWORD`
+    pub fn env_var(&self) -> String {
+        format!("{}_{}", ENV_PREFIX, self.key.replace('.', "__")).to_uppercase()
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{} ({})", self.key, file),
+            None => write!(f, "{} (default)", self.key),
+        }
+    }
+}
+
+/// 配置校验错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ConfigValidationError {
+    /// 配置项为空
+    #[error("{0}: cannot be empty")]
+    Empty(Location),
+
+    /// 数值配置项不大于0
+    #[error("{0}: must be greater than 0")]
+    NotPositive(Location),
+
+    /// 严格校验时缺少必需的配置项
+    #[error("{0}: is required in strict mode")]
+    RequiredInStrictMode(Location),
+
+    /// 严格校验时学生名单为空
+    #[error("{0}: student list cannot be empty in strict mode")]
+    EmptyRoster(Location),
+
+    /// 邮箱格式错误
+    #[error("{location}: invalid email address `{value}`")]
+    InvalidEmail { location: Location, value: String },
+
+    /// 取值不在可选范围内
+    #[error("{location}: invalid value `{value}`")]
+    InvalidChoice {
+        location: Location,
+        value: String,
+        choices: &'static [&'static str],
+    },
+
+    /// 名单文件中的行格式错误
+    #[error("{file}:{line}: {reason}")]
+    MalformedRow {
+        file: String,
+        line: usize,
+        reason: String,
+    },
+
+    /// 同一邮箱的学生出现多次
+    #[error("{position}: duplicate student email `{email}`, first defined at {first}")]
+    DuplicateStudent {
+        position: String,
+        email: String,
+        first: String,
+    },
+
+    /// 同一学号的学生出现多次
+    #[error("{position}: duplicate student id `{id}`, first defined at {first}")]
+    DuplicateId {
+        position: String,
+        id: String,
+        first: String,
+    },
+
+    /// 学生姓名是另一名学生姓名的一部分，按姓名匹配文件时会混淆
+    #[error("{position}: name `{name}` is part of `{other}` at {other_position}")]
+    AmbiguousName {
+        position: String,
+        name: String,
+        other: String,
+        other_position: String,
+    },
+
+    /// 配置值首尾有空白
+    #[error("{position}: leading or trailing whitespace in `{value}`")]
+    SurroundingWhitespace { position: String, value: String },
+
+    /// 邮件主题、文件名或通配符模式无效
+    #[error("{location}: invalid pattern `{pattern}` ({reason})")]
+    InvalidPattern {
+        location: Location,
+        pattern: String,
+        reason: String,
+    },
+
+    /// 同一ID的作业定义出现多次
+    #[error("{location}: duplicate homework id `{id}`, first defined at {first}")]
+    DuplicateHomework {
+        location: Location,
+        id: String,
+        first: String,
+    },
+
+    /// 截止时间格式错误
+    #[error("{location}: invalid deadline `{value}`")]
+    InvalidDeadline { location: Location, value: String },
+
+    /// 文件大小格式错误
+    #[error("{location}: invalid size `{value}`")]
+    InvalidSize { location: Location, value: String },
+}
+
+impl ConfigValidationError {
+    /// 修复建议
+    pub fn suggestion(&self) -> String {
+        match self {
+            Self::Empty(_) => "remove the key to use the default value, or fill in a value".into(),
+            Self::NotPositive(_) => "use a positive integer".into(),
+            Self::RequiredInStrictMode(location) => format!(
+                "set it in local.toml or via the {} environment variable, or set `strict = false` outside production",
+                location.env_var()
+            ),
+            Self::EmptyRoster(_) => {
+                "add students to [stu_config], or set `strict = false` outside production".into()
+            }
+            Self::InvalidEmail { .. } => "use an address like name@example.com".into(),
+            Self::InvalidChoice { choices, .. } => {
+                format!("valid values are: {}", choices.join(", "))
+            }
+            Self::MalformedRow { .. } => "fix the row in the roster file or remove it".into(),
+            Self::DuplicateStudent { .. } => {
+                "keep one entry per student; list extra addresses under `aliases`".into()
+            }
+            Self::DuplicateId { .. } => "student ids must be unique; check for a typo".into(),
+            Self::AmbiguousName { .. } => {
+                "set student ids and ask students to put their id in file names".into()
+            }
+            Self::SurroundingWhitespace { .. } => "remove the extra whitespace".into(),
+            Self::InvalidPattern { location, .. } if location.key.ends_with("filename_pattern") =>
+            {
+                "use {id}, {name}, {class}, {homework}, {pdf|docx}, `*` and `?`, or prefix with `regex:` and capture (?P<id>...) or (?P<name>...)".into()
+            }
+            Self::InvalidPattern { location, .. } if location.key.contains(".required_files[") => {
+                "use file names, paths or globs like `report.pdf`, `src/` or `*.c`".into()
+            }
+            Self::InvalidPattern { location, .. } if location.key.starts_with("check_config.") => {
+                "use globs like `*.pdf`, `~$*` or `__MACOSX`".into()
+            }
+            Self::InvalidPattern { .. } => {
+                "in glob patterns `*` matches anything and `?` one character; prefix with `regex:` for a regular expression".into()
+            }
+            Self::DuplicateHomework { .. } => "give each [[homework]] a unique id".into(),
+            Self::InvalidDeadline { .. } => {
+                "use `YYYY-MM-DD HH:MM` in local time, e.g. `2024-10-01 23:59`".into()
+            }
+            Self::InvalidSize { .. } => "use a size like `512B`, `100KB` or `10MB`".into(),
+        }
+    }
+}
+
+/// 配置校验报告，包含所有校验错误
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub struct ConfigValidationReport {
+    /// 所有校验错误
+    pub errors: Vec<ConfigValidationError>,
+}
+
+impl fmt::Display for ConfigValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "配置校验失败，共 {} 个问题：", self.errors.len())?;
+        for (index, error) in self.errors.iter().enumerate() {
+            write!(f, "\n  {}. {}", index + 1, error)?;
+            write!(f, "\n     hint: {}", error.suggestion())?;
+        }
+        Ok(())
+    }
+}
+
+/// 配置校验结果收集器
+///
+/// 校验过程中不会在第一个错误处停止，而是收集所有问题后统一报告。
+/// 严格模式下额外要求凭据和学生名单等生产环境必需的配置，
+/// 并将名单中的重复与歧义等可疑之处从警告升级为错误
+#[derive(Debug)]
+pub struct Validation<'a> {
+    /// 是否严格校验
+    strict: bool,
+
+    /// 合并后的原始配置，用于查找配置项的来源文件
+    origins: Option<&'a Config>,
+
+    /// 收集到的错误
+    errors: Vec<ConfigValidationError>,
+
+    /// 收集到的警告，不影响配置加载
+    warnings: Vec<ConfigValidationError>,
+}
+
+impl<'a> Validation<'a> {
+    /// 创建收集器
+    ///
+    /// # param
+    /// * `strict` - 是否严格校验
+    pub fn new(strict: bool) -> Self {
+        Self {
+            strict,
+            origins: None,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// 根据环境与配置中的 `strict` 选项创建收集器
+    ///
+    /// 显式配置的 `strict` 优先，否则 `STRICT_ENVIRONMENTS` 中的环境使用严格校验
+    ///
+    /// # param
+    /// * `environment` - 当前环境（APP_ENV）
+    /// * `strict` - 配置中的 `strict` 选项
+    pub fn for_environment(environment: &str, strict: Option<bool>) -> Self {
+        Self::new(strict.unwrap_or_else(|| STRICT_ENVIRONMENTS.contains(&environment)))
+    }
+
+    /// 使用合并后的原始配置定位错误配置项的来源文件
+    pub fn with_origins(mut self, origins: &'a Config) -> Self {
+        self.origins = Some(origins);
+        self
+    }
+
+    /// 是否严格校验
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// 获取配置项位置
+    ///
+    /// # param
+    /// * `key` - 配置项路径
+    pub fn at(&self, key: &str) -> Location {
+        let file = self
+            .origins
+            .and_then(|config| lookup(&config.cache, key))
+            .and_then(|value| value.origin().map(str::to_string));
+
+        Location {
+            key: key.to_string(),
+            file,
+        }
+    }
+
+    /// 条件不成立时记录错误
+    ///
+    /// # param
+    /// * `condition` - 校验条件
+    /// * `key` - 配置项路径
+    /// * `error` - 根据配置项位置构造错误
+    pub fn ensure(
+        &mut self,
+        condition: bool,
+        key: &str,
+        error: impl FnOnce(Location) -> ConfigValidationError,
+    ) {
+        if !condition {
+            let location = self.at(key);
+            self.errors.push(error(location));
+        }
+    }
+
+    /// 记录错误
+    pub fn report(&mut self, error: ConfigValidationError) {
+        self.errors.push(error);
+    }
+
+    /// 记录可疑之处，严格校验时作为错误，否则作为警告
+    pub fn flag(&mut self, problem: ConfigValidationError) {
+        if self.strict {
+            self.errors.push(problem);
+        } else {
+            self.warnings.push(problem);
+        }
+    }
+
+    /// 结束校验
+    ///
+    /// # return
+    /// * `Result<Vec<ConfigValidationError>, ConfigValidationReport>` - 没有错误时返回所有警告，
+    ///   否则返回包含所有错误的报告
+    pub fn finish(self) -> Result<Vec<ConfigValidationError>, ConfigValidationReport> {
+        if self.errors.is_empty() {
+            Ok(self.warnings)
+        } else {
+            Err(ConfigValidationReport {
+                errors: self.errors,
+            })
+        }
+    }
+}
+
+/// 按 `a.b[0].c` 形式的路径查找配置值
+///
+/// `Config::get` 会重新构造值而丢失来源信息，因此直接遍历合并后的配置树
+pub(crate) fn lookup<'v>(root: &'v Value, key: &str) -> Option<&'v Value> {
+    let mut value = root;
+    for segment in key.split('.') {
+        let mut parts = segment.split('[');
+        let name = parts.next()?;
+        value = match &value.kind {
+            ValueKind::Table(table) => table.get(name)?,
+            _ => return None,
+        };
+
+        for index in parts {
+            let index: usize = index.strip_suffix(']')?.parse().ok()?;
+            value = match &value.kind {
+                ValueKind::Array(array) => array.get(index)?,
+                _ => return None,
+            };
+        }
+    }
+
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::File;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn should_locate_key_in_source_file() -> Result<(), Box<dyn std::error::Error>> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let file = tmp_dir.path().join("local.toml");
+        fs::write(
+            &file,
+            "[smtp_config]\nport = 0\n\n[stu_config]\nlist = [{ name = \"a\", email = \"bad\" }]\n",
+        )?;
+        let config = Config::builder()
+            .add_source(File::from(file.as_path()))
+            .build()?;
+        let mut validation = Validation::new(false).with_origins(&config);
+
+        // When
+        validation.ensure(
+            false,
+            "smtp_config.port",
+            ConfigValidationError::NotPositive,
+        );
+        validation.ensure(false, "smtp_config.server", ConfigValidationError::Empty);
+        let report = validation.finish().unwrap_err();
+
+        // Then
+        let [ConfigValidationError::NotPositive(port), ConfigValidationError::Empty(server)] =
+            report.errors.as_slice()
+        else {
+            panic!("unexpected errors: {:?}", report.errors);
+        };
+        assert!(port
+            .file
+            .as_deref()
+            .is_some_and(|f| f.ends_with("local.toml")));
+        assert_eq!(server.file, None);
+        assert_eq!(port.env_var(), "HSCK_SMTP_CONFIG__PORT");
+        assert!(validation_file(&config, "stu_config.list[0].email").is_some());
+        assert!(validation_file(&config, "stu_config.list[1].email").is_none());
+
+        Ok(())
+    }
+
+    fn validation_file(config: &Config, key: &str) -> Option<String> {
+        Validation::new(false).with_origins(config).at(key).file
+    }
+
+    #[test]
+    fn should_flag_as_warning_unless_strict() {
+        // Given
+        let problem = || ConfigValidationError::SurroundingWhitespace {
+            position: "stu_config.list[0].name".into(),
+            value: " 张三".into(),
+        };
+        let mut lenient = Validation::new(false);
+        let mut strict = Validation::new(true);
+
+        // When
+        lenient.flag(problem());
+        strict.flag(problem());
+
+        // Then
+        assert_eq!(lenient.finish(), Ok(vec![problem()]));
+        assert_eq!(strict.finish().unwrap_err().errors, vec![problem()]);
+    }
+
+    #[test]
+    fn should_render_all_errors_with_hints() {
+        // Given
+        let location = |key: &str| Location {
+            key: key.into(),
+            file: Some("cfg/prod.toml".into()),
+        };
+        let report = ConfigValidationReport {
+            errors: vec![
+                ConfigValidationError::RequiredInStrictMode(location("smtp_config.password")),
+                ConfigValidationError::InvalidChoice {
+                    location: location("log_config.level"),
+                    value: "verbose".into(),
+                    choices: &["error", "info"],
+                },
+            ],
+        };
+
+        // When
+        let rendered = report.to_string();
+
+        // Then
+        assert!(rendered.contains("共 2 个问题"));
+        assert!(rendered.contains("1. smtp_config.password (cfg/prod.toml): is required"));
+        assert!(rendered.contains("HSCK_SMTP_CONFIG__PASSWORD"));
+        assert!(rendered.contains("2. log_config.level (cfg/prod.toml): invalid value `verbose`"));
+        assert!(rendered.contains("valid values are: error, info"));
+    }
+}
@@ -0,0 +1,225 @@
+use crate::config::app_config::SmtpConfig;
+use crate::config::stu::Stu;
+use crate::email::email_sender::{build_message, EmailSender};
+use crate::email::rate_limiter::RateLimiter;
+use crate::email::retry::RetryPolicy;
+use crate::email::template::Reminder;
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use lettre::{
+    transport::smtp::PoolConfig, Address, AsyncSmtpTransport, AsyncTransport, Message,
+    Tokio1Executor,
+};
+use log::error;
+
+/// 异步电子邮件发送器
+///
+/// 使用 `AsyncSmtpTransport` 并发发送邮件，同时受最大连接数与每分钟发送数量限制，
+/// 遇到暂时性错误时按退避策略重试。必须在 Tokio 运行时中创建和使用
+#[derive(Debug)]
+pub struct AsyncEmailSender {
+    /// 发件人地址
+    from: Address,
+
+    /// 带连接池的异步SMTP传输
+    mailer: AsyncSmtpTransport<Tokio1Executor>,
+
+    /// 同时发送的最大邮件数
+    max_concurrency: usize,
+
+    /// 发送速率限制器
+    rate_limiter: RateLimiter,
+
+    /// 暂时性错误的重试策略
+    retry_policy: RetryPolicy,
+}
+
+impl AsyncEmailSender {
+    /// 创建一个新的AsyncEmailSender实例
+    ///
+    /// # param
+    /// * `from` - 发件人电子邮件地址
+    /// * `smtp_config` - SMTP服务器配置引用
+    ///
+    /// # return
+    /// * `Result<Self>` - 成功创建的AsyncEmailSender或错误
+    pub fn new(from: &str, smtp_config: &SmtpConfig) -> Result<Self> {
+        let from_address = from.parse().context("无效的发件人邮箱地址")?;
+        let mailer = Self::build_mailer(smtp_config).context("构建邮件发送器失败")?;
+        Ok(Self {
+            from: from_address,
+            mailer,
+            max_concurrency: smtp_config.max_concurrency.max(1),
+            rate_limiter: RateLimiter::per_minute(smtp_config.rate_limit_per_minute),
+            retry_policy: RetryPolicy::from_config(smtp_config),
+        })
+    }
+
+    /// 发送单封邮件，每次尝试前等待速率限制，遇到暂时性错误时自动重试
+    ///
+    /// # param
+    /// * `to` - 收件人电子邮件地址
+    /// * `subject` - 邮件主题
+    /// * `body_text` - 纯文本邮件内容
+    /// * `body_html` - HTML格式邮件内容
+    ///
+    /// # return
+    /// * `Result<()>` - 发送成功或错误
+    pub async fn send(
+        &self,
+        to: &str,
+        subject: &str,
+        body_text: &str,
+        body_html: &str,
+    ) -> Result<()> {
+        let email =
+            build_message(&self.from, to, subject, body_text, body_html).context("构建邮件失败")?;
+
+        self.retry_policy
+            .run(|| async {
+                self.rate_limiter.acquire().await;
+                self.send_email(email.clone(), to).await
+            })
+            .await
+            .context("发送邮件失败")
+    }
+
+    /// 并发发送作业未提交提醒给多个学生
+    ///
+    /// # param
+    /// * `reminder` - 提醒模板与变量
+    /// * `students` - 未提交作业的学生
+    ///
+    /// # return
+    /// * `Vec<(&Stu, Result<()>)>` - 每个学生的发送结果，顺序与输入一致
+    pub async fn send_notifications<'s>(
+        &self,
+        reminder: &Reminder<'_>,
+        students: &'s [Stu],
+    ) -> Vec<(&'s Stu, Result<()>)> {
+        stream::iter(students)
+            .map(|student| async move {
+                let notification = reminder.render(student);
+                let result = self
+                    .send(
+                        &student.email,
+                        &notification.subject,
+                        &notification.text,
+                        &notification.html,
+                    )
+                    .await;
+                (student, result)
+            })
+            .buffered(self.max_concurrency)
+            .collect()
+            .await
+    }
+
+    /// 构建带连接池的异步SMTP传输
+    ///
+    /// # param
+    /// * `smtp_config` - SMTP服务器配置
+    ///
+    /// # return
+    /// * `Result<AsyncSmtpTransport<Tokio1Executor>>` - 配置好的异步传输或错误
+    fn build_mailer(smtp_config: &SmtpConfig) -> Result<AsyncSmtpTransport<Tokio1Executor>> {
+        let mut builder =
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&smtp_config.server)
+                .port(smtp_config.port)
+                .tls(EmailSender::build_tls_mode(smtp_config)?)
+                .pool_config(PoolConfig::new().max_size(smtp_config.max_concurrency.max(1) as u32));
+
+        if !smtp_config.username.is_empty() {
+            builder = builder.credentials(EmailSender::build_credentials(smtp_config));
+        }
+
+        Ok(builder.build())
+    }
+
+    /// 发送单封邮件并处理结果
+    ///
+    /// # param
+    /// * `email` - 要发送的邮件
+    /// * `to` - 收件人地址（用于日志）
+    ///
+    /// # return
+    /// * `Result<()>` - 发送成功或错误
+    async fn send_email(&self, email: Message, to: &str) -> Result<()> {
+        self.mailer.send(email).await.map(|_| ()).map_err(|e| {
+            error!("❌ 发送到 {} 失败: {:?}", to, e);
+            anyhow::Error::new(e)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::app_config::Encryption;
+    use crate::email::fake_smtp::{self, FakeSmtpServer};
+    use crate::email::fixtures::{reminder, students};
+    use std::time::Duration;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_send_notifications_concurrently() -> Result<()> {
+        // Given
+        let server = FakeSmtpServer::start();
+        let mut smtp_config = server.smtp_config(Encryption::None);
+        smtp_config.max_concurrency = 3;
+        server.delay_replies(Duration::from_millis(50));
+        let sender = AsyncEmailSender::new(fake_smtp::USERNAME, &smtp_config)?;
+        let students = students(6);
+
+        // When
+        let results = sender.send_notifications(&reminder(), &students).await;
+
+        // Then
+        assert_eq!(results.len(), 6);
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+        assert_eq!(results[5].0.email, "student5@example.com");
+        assert_eq!(server.mails().len(), 6);
+        assert!(server.peak_transactions() > 1);
+        assert!(server.peak_transactions() <= smtp_config.max_concurrency);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_retry_transient_failures() -> Result<()> {
+        // Given
+        let server = FakeSmtpServer::start();
+        server.reject_recipients(&["451 4.7.1 Greylisted, try again later"]);
+        let sender =
+            AsyncEmailSender::new(fake_smtp::USERNAME, &server.smtp_config(Encryption::None))?;
+        let students = students(2);
+
+        // When
+        let results = sender.send_notifications(&reminder(), &students).await;
+
+        // Then
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+        assert_eq!(server.mails().len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_report_failure_per_student() -> Result<()> {
+        // Given
+        let server = FakeSmtpServer::start();
+        let smtp_config = server.smtp_config(Encryption::None);
+        let sender = AsyncEmailSender::new(fake_smtp::USERNAME, &smtp_config)?;
+        let mut students = students(2);
+        students[0].email = "not-an-address".into();
+
+        // When
+        let results = sender.send_notifications(&reminder(), &students).await;
+
+        // Then
+        assert!(results[0].1.is_err());
+        assert!(results[1].1.is_ok());
+        assert_eq!(server.mails().len(), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use crate::config::stu::Stu;
+use crate::email::email_sender::build_message;
+use crate::email::template::Reminder;
+use anyhow::{Context, Result};
+use lettre::transport::stub::StubTransport;
+use lettre::{Address, FileTransport, Message, Transport};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 未配置SMTP用户名时使用的发件人地址，演练不需要真实的发件人
+pub const PLACEHOLDER_FROM: &str = "hsck@localhost";
+
+/// 演练发送器
+///
+/// 按与 `EmailSender` 完全相同的方式构建提醒邮件，但不连接SMTP服务器：
+/// 未指定输出目录时交给 `StubTransport` 丢弃，指定时由 `FileTransport` 写成 `.eml` 文件
+#[derive(Debug)]
+pub struct DryRunSender {
+    /// 发件人地址
+    from: Address,
+
+    /// 演练使用的传输
+    transport: DryRunTransport,
+}
+
+/// 演练使用的传输
+#[derive(Debug)]
+enum DryRunTransport {
+    /// 丢弃所有邮件
+    Stub(StubTransport),
+
+    /// 将邮件写入目录
+    File {
+        transport: FileTransport,
+        dir: PathBuf,
+    },
+}
+
+/// 演练生成的一封邮件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preview {
+    /// 收件人地址
+    pub to: String,
+
+    /// 邮件主题
+    pub subject: String,
+
+    /// 纯文本内容
+    pub text: String,
+
+    /// 写入的 `.eml` 文件路径，未指定输出目录时为None
+    pub eml_path: Option<PathBuf>,
+}
+
+impl DryRunSender {
+    /// 创建一个新的DryRunSender实例
+    ///
+    /// # param
+    /// * `from` - 发件人电子邮件地址，为空时使用 [`PLACEHOLDER_FROM`]
+    /// * `eml_dir` - `.eml` 文件输出目录，不存在时自动创建
+    ///
+    /// # return
+    /// * `Result<Self>` - 成功创建的DryRunSender或错误
+    pub fn new(from: &str, eml_dir: Option<&Path>) -> Result<Self> {
+        let from = match from.trim().is_empty() {
+            true => PLACEHOLDER_FROM,
+            false => from,
+        };
+        let from_address = from.parse().context("无效的发件人邮箱地址")?;
+        let transport = match eml_dir {
+            Some(dir) => {
+                fs::create_dir_all(dir)
+                    .with_context(|| format!("无法创建邮件输出目录: {}", dir.display()))?;
+                DryRunTransport::File {
+                    transport: FileTransport::new(dir),
+                    dir: dir.to_path_buf(),
+                }
+            }
+            None => DryRunTransport::Stub(StubTransport::new_ok()),
+        };
+
+        Ok(Self {
+            from: from_address,
+            transport,
+        })
+    }
+
+    /// 为每个学生生成提醒邮件
+    ///
+    /// # param
+    /// * `reminder` - 提醒模板与变量
+    /// * `students` - 未提交作业的学生
+    ///
+    /// # return
+    /// * `Vec<(&Stu, Result<Preview>)>` - 每个学生的邮件预览，顺序与输入一致
+    pub fn send_notifications<'s>(
+        &self,
+        reminder: &Reminder<'_>,
+        students: &'s [Stu],
+    ) -> Vec<(&'s Stu, Result<Preview>)> {
+        students
+            .iter()
+            .map(|student| {
+                (
+                    student,
+                    self.send_notification_to_student(reminder, student),
+                )
+            })
+            .collect()
+    }
+
+    /// 为单个学生生成提醒邮件
+    fn send_notification_to_student(&self, reminder: &Reminder, student: &Stu) -> Result<Preview> {
+        let notification = reminder.render(student);
+        let email = build_message(
+            &self.from,
+            &student.email,
+            &notification.subject,
+            &notification.text,
+            &notification.html,
+        )
+        .context("构建邮件失败")?;
+
+        Ok(Preview {
+            to: student.email.clone(),
+            subject: notification.subject,
+            text: notification.text,
+            eml_path: self.deliver(&email)?,
+        })
+    }
+
+    /// 将邮件交给演练传输，返回写入的文件路径
+    fn deliver(&self, email: &Message) -> Result<Option<PathBuf>> {
+        match &self.transport {
+            DryRunTransport::Stub(transport) => {
+                transport.send(email).context("演练发送失败")?;
+                Ok(None)
+            }
+            DryRunTransport::File { transport, dir } => {
+                let id = transport.send(email).context("写入邮件文件失败")?;
+                Ok(Some(dir.join(format!("{}.eml", id))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::email::fixtures::{reminder, students};
+    use tempfile::tempdir;
+
+    #[test]
+    fn should_render_previews_without_connecting() -> Result<()> {
+        // Given
+        let sender = DryRunSender::new("teacher@example.com", None)?;
+        let students = students(2);
+
+        // When
+        let results = sender.send_notifications(&reminder(), &students);
+
+        // Then
+        let previews: Vec<&Preview> = results
+            .iter()
+            .map(|(_, result)| result.as_ref().unwrap())
+            .collect();
+        assert_eq!(previews[0].to, "student0@example.com");
+        assert_eq!(previews[0].subject, "作业1未提交提醒");
+        assert_eq!(previews[1].text, "学生1同学，请提交作业1");
+        assert!(previews.iter().all(|preview| preview.eml_path.is_none()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_write_eml_files() -> Result<()> {
+        // Given
+        let eml_dir = tempdir()?;
+        let sender = DryRunSender::new("teacher@example.com", Some(&eml_dir.path().join("out")))?;
+        let students = students(2);
+
+        // When
+        let results = sender.send_notifications(&reminder(), &students);
+
+        // Then
+        for (student, result) in results {
+            let eml = fs::read_to_string(result?.eml_path.unwrap())?;
+            assert!(eml.contains(&format!("To: {}", student.email)));
+            assert!(eml.contains("From: teacher@example.com"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_invalid_recipient() -> Result<()> {
+        // Given
+        let sender = DryRunSender::new("teacher@example.com", None)?;
+        let mut students = students(2);
+        students[0].email = "not-an-address".into();
+
+        // When
+        let results = sender.send_notifications(&reminder(), &students);
+
+        // Then
+        assert!(results[0].1.is_err());
+        assert!(results[1].1.is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_use_placeholder_sender_without_smtp_username() -> Result<()> {
+        // Given
+        let eml_dir = tempdir()?;
+        let sender = DryRunSender::new("", Some(eml_dir.path()))?;
+        let students = students(2);
+
+        // When
+        let results = sender.send_notifications(&reminder(), &students);
+
+        // Then
+        for (_, result) in results {
+            let eml = fs::read_to_string(result?.eml_path.unwrap())?;
+            assert!(eml.contains(&format!("From: {}", PLACEHOLDER_FROM)));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,906 @@
+use crate::config::app_config::{Encryption, Homework, ImapConfig};
+use crate::config::stu::Stu;
+use crate::email::mime_decoder::{attachment_filename, decode_header_value};
+use crate::email::receive_state::{ReceiveState, STATE_FILE_NAME};
+use crate::email::subject_filter::SubjectFilter;
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset};
+use imap::{ClientBuilder, Connection, ConnectionMode, Session};
+use log::{info, warn};
+use mailparse::MailHeaderMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 每次FETCH的邮件数量，避免单次响应过大
+const FETCH_BATCH_SIZE: usize = 50;
+
+/// 电子邮件接收器
+///
+/// 负责登录IMAP邮箱，查找作业提交邮件并按发件人下载其附件
+#[derive(Debug)]
+pub struct EmailReceiver<'a> {
+    /// IMAP配置引用，避免配置复制
+    imap_config: &'a ImapConfig,
+
+    /// 学生名单，用于根据发件人地址匹配学生
+    students: &'a [Stu],
+}
+
+/// 作业提交邮件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    /// 邮件的 Message-ID
+    pub message_id: Option<String>,
+
+    /// 发件人邮箱地址
+    pub sender: Option<String>,
+
+    /// 邮件头中的发送时间
+    pub date: Option<DateTime<FixedOffset>>,
+
+    /// 邮件中的附件
+    pub attachments: Vec<Attachment>,
+}
+
+/// 邮件附件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    /// 附件文件名
+    pub filename: String,
+
+    /// 解码后的附件内容
+    pub content: Vec<u8>,
+}
+
+/// 接收结果
+#[derive(Debug, Default)]
+pub struct ReceiveSummary {
+    /// 已保存的附件路径
+    pub saved: Vec<PathBuf>,
+
+    /// 无法匹配到学生的发件人地址
+    pub unknown_senders: Vec<String>,
+}
+
+impl<'a> EmailReceiver<'a> {
+    /// 创建一个新的EmailReceiver实例
+    ///
+    /// # param
+    /// * `imap_config` - IMAP服务器配置引用
+    /// * `students` - 学生名单
+    pub fn new(imap_config: &'a ImapConfig, students: &'a [Stu]) -> Self {
+        Self {
+            imap_config,
+            students,
+        }
+    }
+
+    /// 接收作业提交邮件，并将附件下载到作业目录
+    ///
+    /// 带有附件的邮件被视为作业提交邮件，其余邮件会被忽略。指定作业时，
+    /// 只接收主题匹配该作业主题模式的邮件。
+    /// 附件保存在 `<作业下载目录>/<学生姓名>/` 下，作业下载目录见 [`Self::homework_dir`]
+    ///
+    /// # param
+    /// * `homework` - 作业定义
+    ///
+    /// # return
+    /// * `Result<ReceiveSummary>` - 接收结果或错误
+    pub fn receive(&self, homework: Option<&Homework>) -> Result<ReceiveSummary> {
+        let subject_filter = homework
+            .map(|homework| SubjectFilter::for_homework(self.imap_config, homework))
+            .transpose()?;
+
+        let homework_dir = self.homework_dir(homework);
+        fs::create_dir_all(&homework_dir)
+            .with_context(|| format!("无法创建输出目录: {}", homework_dir.display()))?;
+
+        let mut session = self.login()?;
+        let summary =
+            self.download_attachments(&mut session, &homework_dir, subject_filter.as_ref());
+
+        if let Err(e) = session.logout() {
+            warn!("IMAP登出失败: {}", e);
+        }
+
+        summary
+    }
+
+    /// 作业下载目录，也是接收后检查提交的目录
+    ///
+    /// # param
+    /// * `homework` - 作业定义
+    ///
+    /// # return
+    /// * `PathBuf` - 作业设置了 `dir` 时为该目录，否则为 `<out_dir>/<homework>`，
+    ///   未指定作业时为 `<out_dir>`
+    pub fn homework_dir(&self, homework: Option<&Homework>) -> PathBuf {
+        if let Some(dir) = homework.and_then(Homework::submit_dir) {
+            return dir;
+        }
+
+        let out_dir = PathBuf::from(&self.imap_config.out_dir);
+        match homework {
+            Some(homework) => out_dir.join(sanitize_filename(homework.display_name())),
+            None => out_dir,
+        }
+    }
+
+    /// 根据发件人地址查找学生
+    fn find_student(&self, sender: &str) -> Option<&'a Stu> {
+        self.students.iter().find(|stu| stu.owns_address(sender))
+    }
+
+    /// 连接并登录IMAP服务器
+    ///
+    /// # return
+    /// * `Result<Session<Connection>>` - 已登录的IMAP会话或错误
+    fn login(&self) -> Result<Session<Connection>> {
+        let client = ClientBuilder::new(self.imap_config.server.as_str(), self.imap_config.port)
+            .mode(connection_mode(self.imap_config.encryption))
+            .connect()
+            .with_context(|| {
+                format!(
+                    "无法连接IMAP服务器: {}:{}",
+                    self.imap_config.server, self.imap_config.port
+                )
+            })?;
+
+        let session = client
+            .login(&self.imap_config.username, &self.imap_config.password)
+            .map_err(|(e, _)| e)
+            .context("IMAP登录失败")?;
+
+        info!("已登录IMAP服务器: {}", self.imap_config.server);
+        Ok(session)
+    }
+
+    /// 下载邮箱中新邮件的附件
+    ///
+    /// 根据作业目录下的接收状态只拉取上次之后的新邮件，并按 Message-ID 去重。
+    /// 每份作业各自保存接收状态，因此其他作业的邮件不会因本次接收而被跳过；
+    /// 发件人不在学生名单中的邮件会在下次接收时重新处理
+    ///
+    /// # param
+    /// * `session` - 已登录的IMAP会话
+    /// * `homework_dir` - 作业下载目录
+    /// * `subject_filter` - 邮件主题过滤器，为None时接收全部邮件
+    ///
+    /// # return
+    /// * `Result<ReceiveSummary>` - 接收结果或错误
+    fn download_attachments(
+        &self,
+        session: &mut Session<Connection>,
+        homework_dir: &Path,
+        subject_filter: Option<&SubjectFilter>,
+    ) -> Result<ReceiveSummary> {
+        let mailbox = session
+            .select(&self.imap_config.mailbox)
+            .with_context(|| format!("无法打开邮箱: {}", self.imap_config.mailbox))?;
+
+        let state_path = homework_dir.join(STATE_FILE_NAME);
+        let mut state = ReceiveState::load(&state_path)?;
+        if state.uid_validity.is_some() && state.uid_validity != mailbox.uid_validity {
+            warn!(
+                "邮箱 {} 的 UIDVALIDITY 已变化，将重新扫描全部邮件",
+                self.imap_config.mailbox
+            );
+        }
+
+        let query = state.search_query(mailbox.uid_validity);
+        let found = session.uid_search(&query).context("搜索邮件失败")?;
+        let uids = state.pending_uids(found.into_iter().collect());
+        info!(
+            "邮箱 {} 中共有 {} 封新邮件",
+            self.imap_config.mailbox,
+            uids.len()
+        );
+
+        let matched = match subject_filter {
+            Some(filter) => {
+                let matched = Self::filter_by_subject(session, &uids, filter)?;
+                info!("其中 {} 封邮件的主题匹配当前作业", matched.len());
+                matched
+            }
+            None => uids.clone(),
+        };
+
+        let mut summary = ReceiveSummary::default();
+        for batch in matched.chunks(FETCH_BATCH_SIZE) {
+            let uid_set = batch
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(",");
+
+            let fetches = session
+                .uid_fetch(&uid_set, "(UID INTERNALDATE BODY.PEEK[])")
+                .context("获取邮件内容失败")?;
+
+            for fetch in fetches.iter() {
+                let (Some(uid), Some(body)) = (fetch.uid, fetch.body()) else {
+                    continue;
+                };
+
+                let submission = match parse_submission(body) {
+                    Ok(submission) => submission,
+                    Err(e) => {
+                        warn!("解析邮件 UID {} 失败: {}", uid, e);
+                        state.mark_processed(uid, None);
+                        continue;
+                    }
+                };
+
+                let message_id = submission.message_id.clone();
+                if state.is_processed(uid, message_id.as_deref()) {
+                    info!("邮件 {:?} 已处理过，跳过", message_id);
+                    state.mark_processed(uid, message_id.as_deref());
+                    continue;
+                }
+
+                // 优先使用服务器的接收时间，学生无法伪造
+                let received_at = fetch.internal_date().or(submission.date);
+                if self.save_submission(uid, submission, received_at, homework_dir, &mut summary)? {
+                    state.mark_processed(uid, message_id.as_deref());
+                } else {
+                    state.mark_unattributed(uid);
+                }
+            }
+
+            state.save(&state_path)?;
+        }
+
+        // 主题不匹配的邮件同样不再重复检查
+        if let Some(&last_uid) = uids.last() {
+            state.last_uid = state.last_uid.max(last_uid);
+        }
+        state.save(&state_path)?;
+        info!(
+            "共下载 {} 个附件到 {}",
+            summary.saved.len(),
+            homework_dir.display()
+        );
+        Ok(summary)
+    }
+
+    /// 按邮件主题筛选邮件
+    ///
+    /// 只获取邮件头，避免下载与当前作业无关的附件
+    ///
+    /// # param
+    /// * `session` - 已登录的IMAP会话
+    /// * `uids` - 待筛选的邮件UID（升序）
+    /// * `filter` - 邮件主题过滤器
+    ///
+    /// # return
+    /// * `Result<Vec<u32>>` - 主题匹配的邮件UID（升序）或错误
+    fn filter_by_subject(
+        session: &mut Session<Connection>,
+        uids: &[u32],
+        filter: &SubjectFilter,
+    ) -> Result<Vec<u32>> {
+        let mut matched = Vec::new();
+        for batch in uids.chunks(FETCH_BATCH_SIZE) {
+            let uid_set = batch
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(",");
+
+            let fetches = session
+                .uid_fetch(&uid_set, "(UID BODY.PEEK[HEADER])")
+                .context("获取邮件头失败")?;
+
+            for fetch in fetches.iter() {
+                let (Some(uid), Some(header)) = (fetch.uid, fetch.header()) else {
+                    continue;
+                };
+
+                let subject = mailparse::parse_headers(header)
+                    .ok()
+                    .and_then(|(headers, _)| {
+                        headers
+                            .get_first_header("Subject")
+                            .map(|subject| decode_header_value(subject.get_value_raw()))
+                    })
+                    .unwrap_or_default();
+
+                if filter.matches(&subject) {
+                    matched.push(uid);
+                } else {
+                    info!(
+                        "邮件 UID {} 的主题 \"{}\" 不匹配当前作业，跳过",
+                        uid, subject
+                    );
+                }
+            }
+        }
+
+        matched.sort_unstable();
+        Ok(matched)
+    }
+
+    /// 将提交邮件的附件保存到对应学生的目录
+    ///
+    /// 附件的修改时间设为邮件的接收时间，作为判断是否迟交的提交时间
+    ///
+    /// # param
+    /// * `uid` - 邮件UID（用于日志）
+    /// * `submission` - 提交邮件
+    /// * `received_at` - 邮件的接收时间
+    /// * `homework_dir` - 作业下载目录
+    /// * `summary` - 接收结果
+    ///
+    /// # return
+    /// * `Result<bool>` - 邮件是否处理完毕，发件人不在学生名单中时为false
+    fn save_submission(
+        &self,
+        uid: u32,
+        submission: Submission,
+        received_at: Option<DateTime<FixedOffset>>,
+        homework_dir: &Path,
+        summary: &mut ReceiveSummary,
+    ) -> Result<bool> {
+        if submission.attachments.is_empty() {
+            return Ok(true);
+        }
+
+        let sender = submission.sender.unwrap_or_default();
+        let Some(student) = self.find_student(&sender) else {
+            warn!(
+                "发件人 {} 不在学生名单中，跳过邮件 UID {}，更新名单后再次接收即可处理",
+                sender, uid
+            );
+            summary.unknown_senders.push(sender);
+            return Ok(false);
+        };
+
+        let student_dir = homework_dir.join(sanitize_filename(&student.label()));
+        fs::create_dir_all(&student_dir)
+            .with_context(|| format!("无法创建目录: {}", student_dir.display()))?;
+
+        for attachment in &submission.attachments {
+            summary
+                .saved
+                .push(save_attachment(&student_dir, attachment, received_at)?);
+        }
+
+        Ok(true)
+    }
+}
+
+/// 将加密方式映射为IMAP连接模式
+fn connection_mode(encryption: Encryption) -> ConnectionMode {
+    match encryption {
+        Encryption::Tls => ConnectionMode::Tls,
+        Encryption::StartTls => ConnectionMode::StartTls,
+        Encryption::Opportunistic => ConnectionMode::Auto,
+        Encryption::None => ConnectionMode::Plaintext,
+    }
+}
+
+/// 解析原始邮件，提取发件人地址和所有附件
+///
+/// # param
+/// * `raw` - 原始邮件内容（RFC 822）
+///
+/// # return
+/// * `Result<Submission>` - 解析出的提交邮件或解析错误
+pub fn parse_submission(raw: &[u8]) -> Result<Submission> {
+    let mail = mailparse::parse_mail(raw).context("无法解析邮件")?;
+    let message_id = mail
+        .headers
+        .get_first_value("Message-ID")
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty());
+    let sender = mail
+        .headers
+        .get_first_header("From")
+        .and_then(|header| mailparse::addrparse_header(header).ok())
+        .and_then(|addrs| addrs.extract_single_info())
+        .map(|info| info.addr);
+    let date = mail
+        .headers
+        .get_first_value("Date")
+        .and_then(|date| mailparse::dateparse(&date).ok())
+        .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+        .map(|date| date.fixed_offset());
+
+    let mut attachments = Vec::new();
+    for part in mail.parts() {
+        if !part.subparts.is_empty() {
+            continue;
+        }
+
+        let Some(filename) = attachment_filename(part) else {
+            continue;
+        };
+
+        let content = part.get_body_raw().context("无法解码附件内容")?;
+        attachments.push(Attachment { filename, content });
+    }
+
+    Ok(Submission {
+        message_id,
+        sender,
+        date,
+        attachments,
+    })
+}
+
+/// 清理文件名中的路径成分，防止写出输出目录
+fn sanitize_filename(filename: &str) -> String {
+    let name = filename
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim();
+
+    if name.is_empty() || name == "." || name == ".." {
+        "attachment".into()
+    } else {
+        name.into()
+    }
+}
+
+/// 保存附件到指定目录
+///
+/// # param
+/// * `dir` - 保存目录
+/// * `attachment` - 要保存的附件
+/// * `modified` - 附件的修改时间，为None时使用当前时间
+///
+/// # return
+/// * `Result<PathBuf>` - 附件保存路径或错误
+fn save_attachment(
+    dir: &Path,
+    attachment: &Attachment,
+    modified: Option<DateTime<FixedOffset>>,
+) -> Result<PathBuf> {
+    let path = dir.join(sanitize_filename(&attachment.filename));
+    fs::write(&path, &attachment.content)
+        .with_context(|| format!("无法保存附件: {}", path.display()))?;
+    if let Some(modified) = modified {
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(modified.into()))
+            .with_context(|| format!("无法设置附件的修改时间: {}", path.display()))?;
+    }
+
+    info!("已保存附件: {}", path.display());
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::app_config::{CheckConfig, StuConfig};
+    use crate::email::fake_imap::{FakeImapServer, FakeMessage};
+    use crate::utils::check_submissions;
+    use crate::utils::content::ContentRules;
+    use crate::utils::filename_pattern::FilenamePattern;
+    use crate::utils::group::check_groups;
+    use crate::utils::scanner::ScanOptions;
+    use tempfile::tempdir;
+
+    fn students() -> Vec<Stu> {
+        vec![
+            Stu {
+                name: "张三".into(),
+                email: "zhangsan@example.com".into(),
+                aliases: vec!["zs@qq.com".into()],
+                ..Default::default()
+            },
+            Stu {
+                name: "李四".into(),
+                email: "lisi@example.com".into(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn should_parse_sender_and_attachment_from_multipart_mail() -> Result<()> {
+        // Given
+        let raw = FakeMessage::with_attachment(
+            1,
+            "zhangsan@example.com",
+            "作业1",
+            "张三_作业1.txt",
+            b"hello",
+        )
+        .raw;
+
+        // When
+        let submission = parse_submission(&raw)?;
+
+        // Then
+        assert_eq!(submission.sender.as_deref(), Some("zhangsan@example.com"));
+        assert_eq!(
+            submission.date,
+            Some(DateTime::parse_from_rfc3339("2025-10-13T10:00:00+08:00")?)
+        );
+        assert_eq!(submission.attachments.len(), 1);
+        assert_eq!(submission.attachments[0].filename, "张三_作业1.txt");
+        assert_eq!(submission.attachments[0].content, b"hello");
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_strip_path_components_from_filename() {
+        assert_eq!(sanitize_filename("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_filename(r"C:\Users\张三\作业.docx"), "作业.docx");
+        assert_eq!(sanitize_filename(".."), "attachment");
+    }
+
+    #[test]
+    fn should_save_attachments_by_sender() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let students = students();
+        let server = FakeImapServer::start(vec![
+            FakeMessage::with_attachment(1, "ZhangSan@Example.com", "作业1", "hw1.txt", b"one"),
+            FakeMessage::plain(2, "notice@example.com", "通知"),
+            FakeMessage::with_attachment(3, "lisi@example.com", "作业1", "作业.txt", b"three"),
+            FakeMessage::with_attachment(4, "zs@qq.com", "作业1", "补充.txt", b"four"),
+            FakeMessage::with_attachment(5, "stranger@example.com", "作业1", "x.txt", b"five"),
+        ]);
+        let imap_config = server.imap_config(tmp_dir.path());
+
+        // When
+        let summary =
+            EmailReceiver::new(&imap_config, &students).receive(Some(&Homework::named("作业1")))?;
+
+        // Then
+        let homework_dir = tmp_dir.path().join("作业1");
+        assert_eq!(summary.saved.len(), 3);
+        assert_eq!(fs::read(homework_dir.join("张三").join("hw1.txt"))?, b"one");
+        assert_eq!(
+            fs::read(homework_dir.join("张三").join("补充.txt"))?,
+            b"four"
+        );
+        assert_eq!(
+            fs::read(homework_dir.join("李四").join("作业.txt"))?,
+            b"three"
+        );
+        assert_eq!(summary.unknown_senders, vec!["stranger@example.com"]);
+        let modified = fs::metadata(homework_dir.join("张三").join("hw1.txt"))?.modified()?;
+        assert_eq!(
+            DateTime::<chrono::Utc>::from(modified),
+            DateTime::parse_from_rfc3339("2025-10-13T10:00:00+08:00")?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_receive_each_homework_into_its_own_directory() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let students = students();
+        let server = FakeImapServer::start(vec![
+            FakeMessage::with_attachment(1, "zhangsan@example.com", "【作业1】张三", "a.txt", b"1"),
+            FakeMessage::with_attachment(2, "zhangsan@example.com", "【作业2】张三", "b.txt", b"2"),
+            FakeMessage::with_attachment(3, "lisi@example.com", "作业2 李四", "c.txt", b"3"),
+        ]);
+        let mut imap_config = server.imap_config(tmp_dir.path());
+        imap_config.subject_pattern = "【{homework}】*".into();
+        let second_homework = Homework {
+            subject_pattern: r"regex:^(【作业2】|作业2\s)".into(),
+            ..Homework::named("作业2")
+        };
+        let receiver = EmailReceiver::new(&imap_config, &students);
+
+        // When
+        let first = receiver.receive(Some(&Homework::named("作业1")))?;
+        let second = receiver.receive(Some(&second_homework))?;
+
+        // Then
+        assert_eq!(first.saved, vec![tmp_dir.path().join("作业1/张三/a.txt")]);
+        assert_eq!(
+            second.saved,
+            vec![
+                tmp_dir.path().join("作业2/张三/b.txt"),
+                tmp_dir.path().join("作业2/李四/c.txt"),
+            ]
+        );
+        let commands = server.take_commands();
+        assert!(commands.contains(&"UID FETCH 1,2,3 (UID BODY.PEEK[HEADER])".to_string()));
+        assert!(commands.contains(&"UID FETCH 1 (UID INTERNALDATE BODY.PEEK[])".to_string()));
+        assert!(commands.contains(&"UID FETCH 2,3 (UID INTERNALDATE BODY.PEEK[])".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_not_report_state_file_when_checking_received_directory() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let mut stu_config = StuConfig::default();
+        stu_config.list = students();
+        let server = FakeImapServer::start(vec![
+            FakeMessage::with_attachment(
+                1,
+                "zhangsan@example.com",
+                "作业1",
+                "第1组_张三_作业1.txt",
+                b"one",
+            ),
+            FakeMessage::with_attachment(
+                2,
+                "lisi@example.com",
+                "作业1",
+                "第2组_李四_作业1.txt",
+                b"two",
+            ),
+        ]);
+        let imap_config = server.imap_config(tmp_dir.path());
+        let receiver = EmailReceiver::new(&imap_config, &stu_config.list);
+        receiver.receive(Some(&Homework::named("作业1")))?;
+        let homework_dir = receiver.homework_dir(Some(&Homework::named("作业1")));
+        let pattern = FilenamePattern::from_pattern("第?组_{name}_{homework}.txt", Some("作业1"))?;
+        let options = ScanOptions::from_config(&CheckConfig {
+            recursive: true,
+            ..Default::default()
+        })?;
+
+        // When
+        let report = check_submissions(
+            &stu_config,
+            Some(&homework_dir),
+            Some(&pattern),
+            &ContentRules::default(),
+            &options,
+        )?;
+        let groups = check_groups(&stu_config, Some(&homework_dir), 2, &options)?;
+
+        // Then
+        assert!(homework_dir.join(STATE_FILE_NAME).exists());
+        assert_eq!(report.submitted.len(), 2);
+        assert!(report.unmatched.is_empty());
+        assert!(groups.missing.is_empty());
+        assert!(groups.unmatched.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_receive_into_homework_dir_when_set() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let students = students();
+        let server = FakeImapServer::start(vec![FakeMessage::with_attachment(
+            1,
+            "zhangsan@example.com",
+            "作业3",
+            "张三_作业3.txt",
+            b"three",
+        )]);
+        let imap_config = server.imap_config(&tmp_dir.path().join("downloads"));
+        let submit_dir = tmp_dir.path().join("submissions/hw3");
+        let homework = Homework {
+            dir: submit_dir.to_string_lossy().into_owned(),
+            ..Homework::named("作业3")
+        };
+        let receiver = EmailReceiver::new(&imap_config, &students);
+
+        // When
+        let summary = receiver.receive(Some(&homework))?;
+
+        // Then
+        assert_eq!(receiver.homework_dir(Some(&homework)), submit_dir);
+        assert_eq!(summary.saved, [submit_dir.join("张三/张三_作业3.txt")]);
+        assert_eq!(fs::read(submit_dir.join("张三/张三_作业3.txt"))?, b"three");
+        assert!(submit_dir.join(STATE_FILE_NAME).exists());
+        assert!(!tmp_dir.path().join("downloads/作业3").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_save_attachment_with_decoded_chinese_filename() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let students = students();
+        let (gbk, _, _) = encoding_rs::GBK.encode("张三_实验报告.docx");
+        let disposition = [b"attachment; filename=\"".as_slice(), &gbk, b"\""].concat();
+        let server = FakeImapServer::start(vec![
+            FakeMessage::with_raw_attachment(
+                1,
+                "zhangsan@example.com",
+                "作业1",
+                &disposition,
+                b"gbk",
+            ),
+            FakeMessage::with_raw_attachment(
+                2,
+                "lisi@example.com",
+                "作业1",
+                b"attachment; filename*=UTF-8''%E6%9D%8E%E5%9B%9B.pdf",
+                b"rfc2231",
+            ),
+        ]);
+        let imap_config = server.imap_config(tmp_dir.path());
+
+        // When
+        EmailReceiver::new(&imap_config, &students).receive(None)?;
+
+        // Then
+        assert_eq!(
+            fs::read(tmp_dir.path().join("张三").join("张三_实验报告.docx"))?,
+            b"gbk"
+        );
+        assert_eq!(
+            fs::read(tmp_dir.path().join("李四").join("李四.pdf"))?,
+            b"rfc2231"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_only_fetch_new_mail_on_next_run() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let students = students();
+        let server = FakeImapServer::start(vec![FakeMessage::with_attachment(
+            1,
+            "zhangsan@example.com",
+            "作业1",
+            "hw1.txt",
+            b"one",
+        )]);
+        let imap_config = server.imap_config(tmp_dir.path());
+        let receiver = EmailReceiver::new(&imap_config, &students);
+        receiver.receive(Some(&Homework::named("作业1")))?;
+        server.take_commands();
+
+        // When
+        let unchanged = receiver.receive(Some(&Homework::named("作业1")))?;
+        server.deliver(FakeMessage::with_attachment(
+            2,
+            "lisi@example.com",
+            "作业1",
+            "hw1.txt",
+            b"two",
+        ));
+        let delivered = receiver.receive(Some(&Homework::named("作业1")))?;
+
+        // Then
+        assert!(unchanged.saved.is_empty());
+        assert_eq!(delivered.saved.len(), 1);
+        assert!(delivered.saved[0].ends_with("李四/hw1.txt"));
+        let commands = server.take_commands();
+        assert!(commands.contains(&"UID SEARCH UID 2:*".to_string()));
+        assert!(!commands.iter().any(|c| c.starts_with("UID FETCH 1 ")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_retry_unknown_senders_after_roster_update() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let mut students = students();
+        let server = FakeImapServer::start(vec![
+            FakeMessage::with_attachment(1, "wangwu@example.com", "作业1", "hw1.txt", b"one"),
+            FakeMessage::with_attachment(2, "lisi@example.com", "作业1", "hw1.txt", b"two"),
+        ]);
+        let imap_config = server.imap_config(tmp_dir.path());
+        let first =
+            EmailReceiver::new(&imap_config, &students).receive(Some(&Homework::named("作业1")))?;
+        server.take_commands();
+
+        // When
+        students.push(Stu {
+            name: "王五".into(),
+            email: "wangwu@example.com".into(),
+            ..Default::default()
+        });
+        let second =
+            EmailReceiver::new(&imap_config, &students).receive(Some(&Homework::named("作业1")))?;
+        let third =
+            EmailReceiver::new(&imap_config, &students).receive(Some(&Homework::named("作业1")))?;
+
+        // Then
+        assert_eq!(first.unknown_senders, vec!["wangwu@example.com"]);
+        assert_eq!(
+            second.saved,
+            vec![tmp_dir.path().join("作业1/王五/hw1.txt")]
+        );
+        assert!(third.saved.is_empty());
+        let commands = server.take_commands();
+        assert!(commands.contains(&"UID SEARCH UID 1,3:*".to_string()));
+        assert!(commands.contains(&"UID SEARCH UID 3:*".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_rescan_and_deduplicate_after_uid_validity_reset() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let students = students();
+        let first =
+            FakeMessage::with_attachment(1, "zhangsan@example.com", "作业1", "hw1.txt", b"one");
+        let server = FakeImapServer::start(vec![first.clone()]);
+        let imap_config = server.imap_config(tmp_dir.path());
+        let receiver = EmailReceiver::new(&imap_config, &students);
+        receiver.receive(Some(&Homework::named("作业1")))?;
+
+        // When
+        server.rebuild(
+            2,
+            vec![
+                first.renumbered(10),
+                FakeMessage::with_attachment(11, "lisi@example.com", "作业1", "hw1.txt", b"two"),
+            ],
+        );
+        let summary = receiver.receive(Some(&Homework::named("作业1")))?;
+
+        // Then
+        assert_eq!(summary.saved.len(), 1);
+        assert!(summary.saved[0].ends_with("李四/hw1.txt"));
+        assert!(server
+            .take_commands()
+            .contains(&"UID SEARCH ALL".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_deduplicate_after_uid_validity_reset_following_normal_runs() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let students = students();
+        let first =
+            FakeMessage::with_attachment(1, "zhangsan@example.com", "作业1", "hw1.txt", b"one");
+        let second =
+            FakeMessage::with_attachment(2, "lisi@example.com", "作业1", "hw1.txt", b"two");
+        let server = FakeImapServer::start(vec![first.clone()]);
+        let imap_config = server.imap_config(tmp_dir.path());
+        let receiver = EmailReceiver::new(&imap_config, &students);
+        let homework = Homework::named("作业1");
+        receiver.receive(Some(&homework))?;
+        server.deliver(second.clone());
+        receiver.receive(Some(&homework))?;
+        receiver.receive(Some(&homework))?;
+
+        // When
+        server.rebuild(
+            2,
+            vec![
+                first.renumbered(10),
+                second.renumbered(11),
+                FakeMessage::with_attachment(12, "wangwu@example.com", "作业1", "hw1.txt", b"3"),
+            ],
+        );
+        let rescanned = receiver.receive(Some(&homework))?;
+
+        // Then
+        assert!(rescanned.saved.is_empty());
+        assert_eq!(rescanned.unknown_senders, vec!["wangwu@example.com"]);
+        assert!(server
+            .take_commands()
+            .contains(&"UID SEARCH ALL".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_fail_on_wrong_credentials() {
+        // Given
+        let tmp_dir = tempdir().unwrap();
+        let server = FakeImapServer::start(vec![]);
+        let mut imap_config = server.imap_config(tmp_dir.path());
+        imap_config.password = "wrong".into();
+
+        // When
+        let result = EmailReceiver::new(&imap_config, &[]).receive(None);
+
+        // Then
+        assert!(result.is_err());
+    }
+}
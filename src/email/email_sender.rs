@@ -1,11 +1,15 @@
-use crate::config::app_config::SmtpConfig;
+use crate::config::app_config::{Encryption, SmtpConfig};
 use crate::config::stu::Stu;
+use crate::email::rate_limiter::RateLimiter;
+use crate::email::retry::RetryPolicy;
+use crate::email::template::Reminder;
 use anyhow::{Context, Result};
 use lettre::{
     message::{header::ContentType, MultiPart, SinglePart},
     transport::smtp::{
         authentication::Credentials,
         client::{Tls, TlsParameters},
+        PoolConfig,
     },
     Address, Message, SmtpTransport, Transport,
 };
@@ -13,40 +17,47 @@ use log::error;
 
 /// 电子邮件发送器
 ///
-/// 负责构建和发送电子邮件，使用SMTP协议
+/// 负责构建和发送电子邮件，使用SMTP协议。
+/// 发送器在整个运行期间持有同一个带连接池的SMTP传输，所有邮件复用已建立的连接，
+/// 并按配置的每分钟发送数量限制逐封发送，遇到暂时性错误时按退避策略重试
 #[derive(Debug)]
-pub struct EmailSender<'a> {
+pub struct EmailSender {
     /// 发件人地址
     from: Address,
 
-    /// TLS连接模式
-    tls_mode: Tls,
+    /// 带连接池的SMTP传输
+    mailer: SmtpTransport,
 
-    /// SMTP配置引用，避免配置复制
-    smtp_config: &'a SmtpConfig,
+    /// 发送速率限制器
+    rate_limiter: RateLimiter,
+
+    /// 暂时性错误的重试策略
+    retry_policy: RetryPolicy,
 }
 
-impl<'a> EmailSender<'a> {
+impl EmailSender {
     /// 创建一个新的EmailSender实例
     ///
+    /// 此时并不会连接服务器，连接在发送第一封邮件时建立并在之后复用
+    ///
     /// # param
     /// * `from` - 发件人电子邮件地址
     /// * `smtp_config` - SMTP服务器配置引用
     ///
     /// # return
     /// * `Result<Self>` - 成功创建的EmailSender或错误
-    pub fn new(from: &str, smtp_config: &'a SmtpConfig) -> Result<Self> {
-        let tls_params =
-            TlsParameters::new(smtp_config.server.clone()).context("无法创建TLS参数")?;
+    pub fn new(from: &str, smtp_config: &SmtpConfig) -> Result<Self> {
         let from_address = from.parse().context("无效的发件人邮箱地址")?;
+        let mailer = Self::build_mailer(smtp_config).context("构建邮件发送器失败")?;
         Ok(Self {
             from: from_address,
-            tls_mode: Tls::Wrapper(tls_params),
-            smtp_config,
+            mailer,
+            rate_limiter: RateLimiter::per_minute(smtp_config.rate_limit_per_minute),
+            retry_policy: RetryPolicy::from_config(smtp_config),
         })
     }
 
-    /// 发送单封邮件
+    /// 发送单封邮件，遇到暂时性错误时自动重试
     ///
     /// # param
     /// * `to` - 收件人电子邮件地址
@@ -57,13 +68,15 @@ impl<'a> EmailSender<'a> {
     /// # return
     /// * `Result<()>` - 发送成功或错误
     pub fn send(&self, to: &str, subject: &str, body_text: &str, body_html: &str) -> Result<()> {
-        let mailer = self.build_mailer().context("构建邮件发送器失败")?;
-
         let email = self
             .build_email(to, subject, body_text, body_html)
             .context("构建邮件失败")?;
 
-        self.send_email(&mailer, &email, to)
+        self.retry_policy
+            .run_blocking(|| {
+                self.rate_limiter.acquire_blocking();
+                self.send_email(&email, to)
+            })
             .context("发送邮件失败")?;
 
         Ok(())
@@ -72,52 +85,77 @@ impl<'a> EmailSender<'a> {
     /// 发送作业未提交提醒给单个学生
     ///
     /// # param
-    /// * `homework_name` - 作业名称
+    /// * `reminder` - 提醒模板与变量
     /// * `student` - 未提交作业的学生
     ///
     /// # return
     /// * `Result<()>` - 发送成功或错误
-    pub fn send_notification_to_student(&self, homework_name: &str, student: &Stu) -> Result<()> {
-        let subject = "作业未提交提醒";
-        let text = format!(
-            "亲爱的{}同学：\n系统检测到您尚未提交作业<{}>，请及时提交。",
-            student.name, homework_name
-        );
-        let html = format!(
-            r#"<p>亲爱的{}同学：</p>
-            <p>系统检测到您尚未提交作业<strong>{}</strong>，请及时提交。</p> 
-            <p>请发送作业到 1049469060@qq.com。</p>
-            <p>请勿回复这封邮件。</p>"#,
-            student.name, homework_name
-        );
+    pub fn send_notification_to_student(&self, reminder: &Reminder, student: &Stu) -> Result<()> {
+        let notification = reminder.render(student);
 
-        self.send(&student.email, subject, &text, &html)
+        self.send(
+            &student.email,
+            &notification.subject,
+            &notification.text,
+            &notification.html,
+        )
     }
 
-    /// 构建SMTP邮件发送器
+    /// 根据配置的加密方式构建TLS连接模式
+    ///
+    /// # param
+    /// * `smtp_config` - SMTP服务器配置
+    ///
+    /// # return
+    /// * `Result<Tls>` - TLS连接模式或错误
+    pub(crate) fn build_tls_mode(smtp_config: &SmtpConfig) -> Result<Tls> {
+        if smtp_config.encryption == Encryption::None {
+            return Ok(Tls::None);
+        }
+
+        let tls_params =
+            TlsParameters::new(smtp_config.server.clone()).context("无法创建TLS参数")?;
+        let tls_mode = match smtp_config.encryption {
+            Encryption::Tls => Tls::Wrapper(tls_params),
+            Encryption::StartTls => Tls::Required(tls_params),
+            Encryption::Opportunistic => Tls::Opportunistic(tls_params),
+            Encryption::None => Tls::None,
+        };
+
+        Ok(tls_mode)
+    }
+
+    /// 构建带连接池的SMTP邮件发送器
+    ///
+    /// 未配置用户名时不进行认证，以支持无需认证的本地中继
+    ///
+    /// # param
+    /// * `smtp_config` - SMTP服务器配置
     ///
     /// # return
     /// * `Result<SmtpTransport>` - 配置好的邮件发送器或错误
-    fn build_mailer(&self) -> Result<SmtpTransport> {
-        let trans = SmtpTransport::relay(&self.smtp_config.server)
-            .context("创建SMTP传输失败")?
-            .port(self.smtp_config.port)
-            .tls(self.tls_mode.clone())
-            .credentials(self.build_credentials())
-            .build();
+    fn build_mailer(smtp_config: &SmtpConfig) -> Result<SmtpTransport> {
+        let mut builder = SmtpTransport::builder_dangerous(&smtp_config.server)
+            .port(smtp_config.port)
+            .tls(Self::build_tls_mode(smtp_config)?)
+            .pool_config(PoolConfig::new().max_size(1));
+
+        if !smtp_config.username.is_empty() {
+            builder = builder.credentials(Self::build_credentials(smtp_config));
+        }
 
-        Ok(trans)
+        Ok(builder.build())
     }
 
     /// 构建SMTP认证凭据
     ///
+    /// # param
+    /// * `smtp_config` - SMTP服务器配置
+    ///
     /// # return
     /// * `Credentials` - SMTP认证凭据
-    fn build_credentials(&self) -> Credentials {
-        Credentials::new(
-            self.smtp_config.username.clone(),
-            self.smtp_config.password.clone(),
-        )
+    pub(crate) fn build_credentials(smtp_config: &SmtpConfig) -> Credentials {
+        Credentials::new(smtp_config.username.clone(), smtp_config.password.clone())
     }
 
     /// 构建邮件对象
@@ -137,47 +175,213 @@ impl<'a> EmailSender<'a> {
         body_text: &str,
         body_html: &str,
     ) -> Result<Message> {
-        Message::builder()
-            .from(self.from.clone().into())
-            .to(to.parse().context("无效的收件人地址")?)
-            .subject(subject)
-            .multipart(
-                MultiPart::alternative()
-                    .singlepart(
-                        SinglePart::builder()
-                            .content_type(ContentType::TEXT_PLAIN)
-                            .body(String::from(body_text)),
-                    )
-                    .singlepart(
-                        SinglePart::builder()
-                            .content_type(ContentType::TEXT_HTML)
-                            .body(String::from(body_html)),
-                    ),
-            )
-            .context("构建邮件内容失败")
-    }
-
-    /// 发送单封邮件并处理结果
+        build_message(&self.from, to, subject, body_text, body_html)
+    }
+
+    /// 通过已建立的连接发送单封邮件并处理结果
     ///
     /// # param
-    /// * `mailer` - SMTP发送器
     /// * `email` - 要发送的邮件
     /// * `to` - 收件人地址（用于日志）
     ///
     /// # return
     /// * `Result<()>` - 发送成功或错误
-    fn send_email(&self, mailer: &SmtpTransport, email: &Message, to: &str) -> Result<()> {
-        mailer.send(email).map(|_| ()).map_err(|e| {
+    fn send_email(&self, email: &Message, to: &str) -> Result<()> {
+        self.mailer.send(email).map(|_| ()).map_err(|e| {
             error!("❌ 发送到 {} 失败: {:?}", to, e);
             anyhow::Error::new(e)
         })
     }
 }
 
+/// 构建包含纯文本与HTML两种格式的邮件对象
+///
+/// # param
+/// * `from` - 发件人地址
+/// * `to` - 收件人地址
+/// * `subject` - 邮件主题
+/// * `body_text` - 纯文本内容
+/// * `body_html` - HTML内容
+///
+/// # return
+/// * `Result<Message>` - 构建好的邮件对象或错误
+pub(crate) fn build_message(
+    from: &Address,
+    to: &str,
+    subject: &str,
+    body_text: &str,
+    body_html: &str,
+) -> Result<Message> {
+    Message::builder()
+        .from(from.clone().into())
+        .to(to.parse().context("无效的收件人地址")?)
+        .subject(subject)
+        .multipart(
+            MultiPart::alternative()
+                .singlepart(
+                    SinglePart::builder()
+                        .content_type(ContentType::TEXT_PLAIN)
+                        .body(String::from(body_text)),
+                )
+                .singlepart(
+                    SinglePart::builder()
+                        .content_type(ContentType::TEXT_HTML)
+                        .body(String::from(body_html)),
+                ),
+        )
+        .context("构建邮件内容失败")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::loader;
+    use crate::email::fake_smtp::{self, FakeSmtpServer};
+    use crate::email::retry;
+
+    #[test]
+    fn should_send_over_plaintext_when_encryption_is_none() -> Result<()> {
+        // Given
+        let server = FakeSmtpServer::start();
+        let smtp_config = server.smtp_config(Encryption::None);
+        let sender = EmailSender::new(fake_smtp::USERNAME, &smtp_config)?;
+
+        // When
+        sender.send("student@example.com", "测试", "正文", "<p>正文</p>")?;
+
+        // Then
+        let mails = server.mails();
+        assert_eq!(mails.len(), 1);
+        assert_eq!(mails[0].from, fake_smtp::USERNAME);
+        assert_eq!(mails[0].to, vec!["student@example.com"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_fall_back_to_plaintext_when_starttls_is_opportunistic() -> Result<()> {
+        // Given
+        let server = FakeSmtpServer::start();
+        let smtp_config = server.smtp_config(Encryption::Opportunistic);
+        let sender = EmailSender::new(fake_smtp::USERNAME, &smtp_config)?;
+
+        // When
+        sender.send("student@example.com", "测试", "正文", "<p>正文</p>")?;
+
+        // Then
+        assert_eq!(server.mails().len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_refuse_plaintext_when_starttls_is_required() -> Result<()> {
+        // Given
+        let server = FakeSmtpServer::start();
+        let smtp_config = server.smtp_config(Encryption::StartTls);
+        let sender = EmailSender::new(fake_smtp::USERNAME, &smtp_config)?;
+
+        // When
+        let result = sender.send("student@example.com", "测试", "正文", "<p>正文</p>");
+
+        // Then
+        assert!(result.is_err());
+        assert!(server.mails().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_reuse_one_connection_for_all_messages() -> Result<()> {
+        // Given
+        let server = FakeSmtpServer::start();
+        let smtp_config = server.smtp_config(Encryption::None);
+        let sender = EmailSender::new(fake_smtp::USERNAME, &smtp_config)?;
+
+        // When
+        for i in 0..5 {
+            sender.send(
+                &format!("student{}@example.com", i),
+                "测试",
+                "正文",
+                "<p>正文</p>",
+            )?;
+        }
+
+        // Then
+        assert_eq!(server.mails().len(), 5);
+        assert_eq!(server.connections(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_retry_transient_failures() -> Result<()> {
+        // Given
+        let server = FakeSmtpServer::start();
+        server.reject_recipients(&["451 4.7.1 Greylisted, try again later", "421 Busy"]);
+        let sender = EmailSender::new(fake_smtp::USERNAME, &server.smtp_config(Encryption::None))?;
+
+        // When
+        sender.send("student@example.com", "测试", "正文", "<p>正文</p>")?;
+
+        // Then
+        assert_eq!(server.pending_rejections(), 0);
+        assert_eq!(server.mails().len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_not_retry_permanent_failures() -> Result<()> {
+        // Given
+        let server = FakeSmtpServer::start();
+        server.reject_recipients(&["550 5.1.1 No such user", "550 5.1.1 No such user"]);
+        let sender = EmailSender::new(fake_smtp::USERNAME, &server.smtp_config(Encryption::None))?;
+
+        // When
+        let result = sender.send("nobody@example.com", "测试", "正文", "<p>正文</p>");
+
+        // Then
+        assert!(result.is_err());
+        assert_eq!(server.pending_rejections(), 1);
+        assert!(server.mails().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_give_up_after_max_attempts() -> Result<()> {
+        // Given
+        let server = FakeSmtpServer::start();
+        server.reject_recipients(&["451 Try again"; 4]);
+        let sender = EmailSender::new(fake_smtp::USERNAME, &server.smtp_config(Encryption::None))?;
+
+        // When
+        let result = sender.send("student@example.com", "测试", "正文", "<p>正文</p>");
+
+        // Then
+        assert!(result.is_err_and(|e| retry::is_transient(&e)));
+        assert_eq!(server.pending_rejections(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_map_encryption_to_tls_mode() -> Result<()> {
+        let server = FakeSmtpServer::start();
+        let tls_mode = |encryption| EmailSender::build_tls_mode(&server.smtp_config(encryption));
+
+        assert!(matches!(tls_mode(Encryption::Tls)?, Tls::Wrapper(_)));
+        assert!(matches!(tls_mode(Encryption::StartTls)?, Tls::Required(_)));
+        assert!(matches!(
+            tls_mode(Encryption::Opportunistic)?,
+            Tls::Opportunistic(_)
+        ));
+        assert!(matches!(tls_mode(Encryption::None)?, Tls::None));
+
+        Ok(())
+    }
 
     #[test]
     #[ignore]
@@ -0,0 +1,335 @@
+//! 测试用的本地IMAP服务器
+//!
+//! 仅实现客户端用到的最小命令子集：LOGIN、SELECT、UID SEARCH、UID FETCH、LOGOUT
+
+use std::collections::BTreeSet;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::config::app_config::{Encryption, ImapConfig};
+
+pub const USERNAME: &str = "teacher@example.com";
+pub const PASSWORD: &str = "secret";
+
+/// 测试邮件
+#[derive(Debug, Clone)]
+pub struct FakeMessage {
+    pub uid: u32,
+    pub internal_date: String,
+    pub raw: Vec<u8>,
+}
+
+impl FakeMessage {
+    /// 构建一封带有单个附件的邮件
+    pub fn with_attachment(
+        uid: u32,
+        from: &str,
+        subject: &str,
+        filename: &str,
+        content: &[u8],
+    ) -> Self {
+        let disposition = format!("attachment; filename=\"{}\"", filename);
+        Self::with_raw_attachment(uid, from, subject, disposition.as_bytes(), content)
+    }
+
+    /// 构建一封带有单个附件的邮件，Content-Disposition 头按原样写入
+    pub fn with_raw_attachment(
+        uid: u32,
+        from: &str,
+        subject: &str,
+        disposition: &[u8],
+        content: &[u8],
+    ) -> Self {
+        let mut raw = format!(
+            "From: {from}\r\n\
+             To: {USERNAME}\r\n\
+             Subject: {subject}\r\n\
+             Message-ID: <{uid}@fake.example.com>\r\n\
+             Date: Mon, 13 Oct 2025 10:00:00 +0800\r\n\
+             MIME-Version: 1.0\r\n\
+             Content-Type: multipart/mixed; boundary=\"BOUNDARY\"\r\n\
+             \r\n\
+             --BOUNDARY\r\n\
+             Content-Type: text/plain; charset=utf-8\r\n\
+             \r\n\
+             作业见附件\r\n\
+             --BOUNDARY\r\n\
+             Content-Type: application/octet-stream\r\n\
+             Content-Transfer-Encoding: base64\r\n\
+             Content-Disposition: "
+        )
+        .into_bytes();
+        raw.extend_from_slice(disposition);
+        raw.extend_from_slice(b"\r\n\r\n");
+        raw.extend_from_slice(base64(content).as_bytes());
+        raw.extend_from_slice(b"\r\n--BOUNDARY--\r\n");
+
+        Self {
+            uid,
+            internal_date: "13-Oct-2025 10:00:00 +0800".into(),
+            raw,
+        }
+    }
+
+    /// 构建一封没有附件的纯文本邮件
+    pub fn plain(uid: u32, from: &str, subject: &str) -> Self {
+        let raw = format!(
+            "From: {from}\r\n\
+             To: {USERNAME}\r\n\
+             Subject: {subject}\r\n\
+             Message-ID: <{uid}@fake.example.com>\r\n\
+             Date: Mon, 13 Oct 2025 10:00:00 +0800\r\n\
+             Content-Type: text/plain; charset=utf-8\r\n\
+             \r\n\
+             没有附件\r\n"
+        )
+        .into_bytes();
+
+        Self {
+            uid,
+            internal_date: "13-Oct-2025 10:00:00 +0800".into(),
+            raw,
+        }
+    }
+
+    /// 复制邮件并使用新的UID，Message-ID 保持不变
+    pub fn renumbered(&self, uid: u32) -> Self {
+        Self {
+            uid,
+            ..self.clone()
+        }
+    }
+}
+
+/// 服务器上的邮箱
+#[derive(Debug)]
+struct Mailbox {
+    uid_validity: u32,
+    messages: Vec<FakeMessage>,
+}
+
+/// 本地IMAP服务器，在后台线程中处理连接
+pub struct FakeImapServer {
+    port: u16,
+    mailbox: Arc<Mutex<Mailbox>>,
+    commands: Arc<Mutex<Vec<String>>>,
+}
+
+impl FakeImapServer {
+    /// 启动服务器，服务器会持续接受连接直到测试进程结束
+    pub fn start(messages: Vec<FakeMessage>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mailbox = Arc::new(Mutex::new(Mailbox {
+            uid_validity: 1,
+            messages,
+        }));
+        let commands = Arc::new(Mutex::new(Vec::new()));
+
+        let (shared_mailbox, log) = (Arc::clone(&mailbox), Arc::clone(&commands));
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (mailbox, log) = (Arc::clone(&shared_mailbox), Arc::clone(&log));
+                thread::spawn(move || serve(stream, &mailbox, &log));
+            }
+        });
+
+        Self {
+            port,
+            mailbox,
+            commands,
+        }
+    }
+
+    /// 向邮箱投递一封新邮件
+    pub fn deliver(&self, message: FakeMessage) {
+        self.mailbox.lock().unwrap().messages.push(message);
+    }
+
+    /// 重建邮箱：更换 UIDVALIDITY 并替换全部邮件
+    pub fn rebuild(&self, uid_validity: u32, messages: Vec<FakeMessage>) {
+        let mut mailbox = self.mailbox.lock().unwrap();
+        mailbox.uid_validity = uid_validity;
+        mailbox.messages = messages;
+    }
+
+    /// 服务器收到的所有命令（不含标签），并清空记录
+    pub fn take_commands(&self) -> Vec<String> {
+        std::mem::take(&mut *self.commands.lock().unwrap())
+    }
+
+    /// 生成连接到该服务器的IMAP配置
+    pub fn imap_config(&self, out_dir: &Path) -> ImapConfig {
+        ImapConfig {
+            server: "127.0.0.1".into(),
+            port: self.port,
+            username: USERNAME.into(),
+            password: PASSWORD.into(),
+            encryption: Encryption::None,
+            mailbox: "INBOX".into(),
+            subject_pattern: "*{homework}*".into(),
+            out_dir: out_dir.to_string_lossy().into_owned(),
+        }
+    }
+}
+
+fn serve(stream: TcpStream, mailbox: &Mutex<Mailbox>, log: &Mutex<Vec<String>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    writer.write_all(b"* OK fake IMAP ready\r\n").unwrap();
+
+    let mut line = String::new();
+    while reader.read_line(&mut line).unwrap_or(0) > 0 {
+        let (tag, command) = line
+            .trim_end()
+            .split_once(' ')
+            .unwrap_or((line.trim_end(), ""));
+        let tag = tag.to_string();
+        let command = command.to_string();
+        line.clear();
+        log.lock().unwrap().push(command.clone());
+
+        let (uid_validity, messages) = {
+            let mailbox = mailbox.lock().unwrap();
+            (mailbox.uid_validity, mailbox.messages.clone())
+        };
+        let messages = messages.as_slice();
+
+        let upper = command.to_uppercase();
+        let response = if upper.starts_with("CAPABILITY") {
+            b"* CAPABILITY IMAP4rev1\r\n".to_vec()
+        } else if upper.starts_with("LOGIN") {
+            let expected = format!("LOGIN \"{}\" \"{}\"", USERNAME, PASSWORD);
+            if command != expected {
+                writer
+                    .write_all(format!("{tag} NO [AUTHENTICATIONFAILED] invalid\r\n").as_bytes())
+                    .unwrap();
+                continue;
+            }
+            Vec::new()
+        } else if upper.starts_with("SELECT") {
+            format!(
+                "* {} EXISTS\r\n* 0 RECENT\r\n* OK [UIDVALIDITY {}] UIDs valid\r\n\
+                 * FLAGS (\\Seen)\r\n",
+                messages.len(),
+                uid_validity
+            )
+            .into_bytes()
+        } else if let Some(criteria) = upper.strip_prefix("UID SEARCH") {
+            let uids = matching_uids(criteria, messages);
+            let list = uids.iter().map(u32::to_string).collect::<Vec<_>>();
+            format!("* SEARCH {}\r\n", list.join(" ")).into_bytes()
+        } else if let Some(args) = upper.strip_prefix("UID FETCH ") {
+            let (set, items) = args.split_once(' ').unwrap_or((args, ""));
+            fetch_response(set, items, messages)
+        } else if upper.starts_with("LOGOUT") {
+            writer
+                .write_all(format!("* BYE\r\n{tag} OK LOGOUT completed\r\n").as_bytes())
+                .unwrap();
+            return;
+        } else {
+            writer
+                .write_all(format!("{tag} BAD unsupported\r\n").as_bytes())
+                .unwrap();
+            continue;
+        };
+
+        writer.write_all(&response).unwrap();
+        writer
+            .write_all(format!("{tag} OK completed\r\n").as_bytes())
+            .unwrap();
+    }
+}
+
+/// 解析 UID SEARCH 条件，仅支持 ALL 与 UID <set>
+fn matching_uids(criteria: &str, messages: &[FakeMessage]) -> BTreeSet<u32> {
+    let criteria = criteria.trim();
+    match criteria.strip_prefix("UID ") {
+        Some(set) => uids_in_set(set, messages),
+        None => messages.iter().map(|m| m.uid).collect(),
+    }
+}
+
+/// 解析形如 `1,3:5,7:*` 的UID集合
+fn uids_in_set(set: &str, messages: &[FakeMessage]) -> BTreeSet<u32> {
+    let max = messages.iter().map(|m| m.uid).max().unwrap_or(0);
+    let parse = |s: &str| if s == "*" { max } else { s.parse().unwrap() };
+
+    let mut result = BTreeSet::new();
+    for range in set.split(',') {
+        let (lo, hi) = match range.split_once(':') {
+            Some((a, b)) => (parse(a), parse(b)),
+            None => (parse(range), parse(range)),
+        };
+        let (lo, hi) = (lo.min(hi), lo.max(hi));
+        for message in messages {
+            if message.uid >= lo && message.uid <= hi {
+                result.insert(message.uid);
+            }
+        }
+    }
+
+    result
+}
+
+/// 生成 UID FETCH 响应，`BODY.PEEK[HEADER]` 只返回邮件头，其余情况返回完整邮件
+fn fetch_response(set: &str, items: &str, messages: &[FakeMessage]) -> Vec<u8> {
+    let uids = uids_in_set(set, messages);
+    let mut response = Vec::new();
+    for (seq, message) in messages.iter().enumerate() {
+        if !uids.contains(&message.uid) {
+            continue;
+        }
+
+        let (section, data) = if items.contains("BODY.PEEK[HEADER]") {
+            let end = message
+                .raw
+                .windows(4)
+                .position(|w| w == b"\r\n\r\n")
+                .map_or(message.raw.len(), |pos| pos + 4);
+            ("BODY[HEADER]", &message.raw[..end])
+        } else {
+            ("BODY[]", message.raw.as_slice())
+        };
+
+        response.extend_from_slice(
+            format!(
+                "* {} FETCH (UID {} INTERNALDATE \"{}\" {} {{{}}}\r\n",
+                seq + 1,
+                message.uid,
+                message.internal_date,
+                section,
+                data.len()
+            )
+            .as_bytes(),
+        );
+        response.extend_from_slice(data);
+        response.extend_from_slice(b")\r\n");
+    }
+    response
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::new();
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
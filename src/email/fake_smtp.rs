@@ -0,0 +1,227 @@
+//! 测试用的本地明文SMTP服务器
+//!
+//! 接受任意凭据，不支持 STARTTLS，记录收到的每一封邮件和同时进行的邮件事务数峰值，
+//! 并可以让接下来的若干次 `RCPT TO` 返回指定的错误响应
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::config::app_config::{Encryption, SmtpConfig};
+
+pub const USERNAME: &str = "teacher@example.com";
+pub const PASSWORD: &str = "secret";
+
+/// 服务器收到的邮件
+#[derive(Debug, Clone, Default)]
+pub struct ReceivedMail {
+    pub from: String,
+    pub to: Vec<String>,
+    pub data: String,
+}
+
+/// 本地SMTP服务器，在后台线程中处理连接
+pub struct FakeSmtpServer {
+    port: u16,
+    mails: Arc<Mutex<Vec<ReceivedMail>>>,
+    connections: Arc<Mutex<usize>>,
+    transactions: Arc<Transactions>,
+    rejections: Arc<Mutex<VecDeque<String>>>,
+}
+
+/// 邮件事务计数，记录从 `MAIL FROM` 到邮件接收完毕之间的事务数、峰值以及每封邮件的应答延迟
+#[derive(Default)]
+struct Transactions {
+    active: AtomicUsize,
+    peak: AtomicUsize,
+    delay_ms: AtomicU64,
+}
+
+impl Transactions {
+    fn open(&self) {
+        let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(active, Ordering::SeqCst);
+    }
+
+    fn close(&self) {
+        self.active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl FakeSmtpServer {
+    /// 启动服务器，服务器会持续接受连接直到测试进程结束
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mails = Arc::new(Mutex::new(Vec::new()));
+        let connections = Arc::new(Mutex::new(0));
+        let transactions = Arc::new(Transactions::default());
+        let rejections = Arc::new(Mutex::new(VecDeque::new()));
+
+        let (shared_mails, shared_connections, shared_transactions, shared_rejections) = (
+            Arc::clone(&mails),
+            Arc::clone(&connections),
+            Arc::clone(&transactions),
+            Arc::clone(&rejections),
+        );
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                *shared_connections.lock().unwrap() += 1;
+                let mails = Arc::clone(&shared_mails);
+                let transactions = Arc::clone(&shared_transactions);
+                let rejections = Arc::clone(&shared_rejections);
+                thread::spawn(move || serve(stream, &mails, &transactions, &rejections));
+            }
+        });
+
+        Self {
+            port,
+            mails,
+            connections,
+            transactions,
+            rejections,
+        }
+    }
+
+    /// 生成连接到该服务器的SMTP配置
+    pub fn smtp_config(&self, encryption: Encryption) -> SmtpConfig {
+        SmtpConfig {
+            server: "127.0.0.1".into(),
+            port: self.port,
+            username: USERNAME.into(),
+            password: PASSWORD.into(),
+            encryption,
+            max_concurrency: 2,
+            rate_limit_per_minute: 0,
+            retry_attempts: 3,
+            retry_backoff_ms: 1,
+        }
+    }
+
+    /// 让接下来的 `RCPT TO` 依次返回给定的响应，如 `451 4.7.1 Greylisted`
+    pub fn reject_recipients(&self, replies: &[&str]) {
+        let mut rejections = self.rejections.lock().unwrap();
+        rejections.extend(replies.iter().map(|reply| format!("{}\r\n", reply)));
+    }
+
+    /// 收到每封邮件后等待一段时间再应答，让并发发送的邮件事务在时间上重叠
+    pub fn delay_replies(&self, delay: Duration) {
+        self.transactions
+            .delay_ms
+            .store(delay.as_millis() as u64, Ordering::SeqCst);
+    }
+
+    /// 尚未使用的错误响应数量
+    pub fn pending_rejections(&self) -> usize {
+        self.rejections.lock().unwrap().len()
+    }
+
+    /// 服务器收到的所有邮件
+    pub fn mails(&self) -> Vec<ReceivedMail> {
+        self.mails.lock().unwrap().clone()
+    }
+
+    /// 服务器接受的连接数
+    pub fn connections(&self) -> usize {
+        *self.connections.lock().unwrap()
+    }
+
+    /// 同时进行的邮件事务数的峰值
+    pub fn peak_transactions(&self) -> usize {
+        self.transactions.peak.load(Ordering::SeqCst)
+    }
+}
+
+fn serve(
+    stream: TcpStream,
+    mails: &Mutex<Vec<ReceivedMail>>,
+    transactions: &Transactions,
+    rejections: &Mutex<VecDeque<String>>,
+) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    reply(&mut writer, "220 fake SMTP ready\r\n");
+
+    let mut mail = ReceivedMail::default();
+    let mut in_transaction = false;
+    let mut line = String::new();
+    while reader.read_line(&mut line).unwrap_or(0) > 0 {
+        let command = line.trim_end().to_string();
+        line.clear();
+        let upper = command.to_uppercase();
+
+        if upper.starts_with("EHLO") || upper.starts_with("HELO") {
+            reply(
+                &mut writer,
+                "250-localhost\r\n250-AUTH PLAIN LOGIN\r\n250 OK\r\n",
+            );
+        } else if upper.starts_with("AUTH") {
+            reply(&mut writer, "235 Authentication successful\r\n");
+        } else if upper.starts_with("MAIL FROM:") {
+            if !std::mem::replace(&mut in_transaction, true) {
+                transactions.open();
+            }
+            mail = ReceivedMail {
+                from: address(&command),
+                ..Default::default()
+            };
+            reply(&mut writer, "250 OK\r\n");
+        } else if upper.starts_with("RCPT TO:") {
+            match rejections.lock().unwrap().pop_front() {
+                Some(rejection) => reply(&mut writer, &rejection),
+                None => {
+                    mail.to.push(address(&command));
+                    reply(&mut writer, "250 OK\r\n");
+                }
+            }
+        } else if upper == "DATA" {
+            reply(&mut writer, "354 End data with <CR><LF>.<CR><LF>\r\n");
+            let mut data = String::new();
+            while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                if line == ".\r\n" {
+                    line.clear();
+                    break;
+                }
+                data.push_str(&line);
+                line.clear();
+            }
+            mail.data = data;
+            mails.lock().unwrap().push(std::mem::take(&mut mail));
+            thread::sleep(Duration::from_millis(
+                transactions.delay_ms.load(Ordering::SeqCst),
+            ));
+            if std::mem::take(&mut in_transaction) {
+                transactions.close();
+            }
+            reply(&mut writer, "250 OK queued\r\n");
+        } else if upper == "RSET" || upper == "NOOP" {
+            if upper == "RSET" && std::mem::take(&mut in_transaction) {
+                transactions.close();
+            }
+            reply(&mut writer, "250 OK\r\n");
+        } else if upper == "QUIT" {
+            reply(&mut writer, "221 Bye\r\n");
+            break;
+        } else {
+            reply(&mut writer, "502 Command not implemented\r\n");
+        }
+    }
+    if in_transaction {
+        transactions.close();
+    }
+}
+
+fn reply(writer: &mut TcpStream, text: &str) {
+    let _ = writer.write_all(text.as_bytes());
+}
+
+/// 提取 `MAIL FROM:<addr>` 或 `RCPT TO:<addr>` 中的地址
+fn address(command: &str) -> String {
+    let start = command.find('<').map_or(0, |i| i + 1);
+    let end = command.find('>').unwrap_or(command.len());
+    command[start..end].to_string()
+}
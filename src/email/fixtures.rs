@@ -0,0 +1,43 @@
+//! 发送器测试共用的学生名单与提醒邮件
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+use crate::config::stu::Stu;
+use crate::email::template::{Reminder, ReminderTemplate};
+
+/// 未提交提醒中没有无效原因
+static NO_REASONS: Lazy<HashMap<String, String>> = Lazy::new(HashMap::new);
+
+static TEMPLATE: Lazy<ReminderTemplate> = Lazy::new(|| {
+    ReminderTemplate::new(
+        "{{homework}}未提交提醒",
+        "{{student.name}}同学，请提交{{homework}}",
+        "<p>{{student.name}}</p>",
+    )
+    .unwrap()
+});
+
+/// 生成 `count` 个学生，姓名为 `学生<i>`，邮箱为 `student<i>@example.com`
+pub fn students(count: usize) -> Vec<Stu> {
+    (0..count)
+        .map(|i| Stu {
+            name: format!("学生{}", i),
+            email: format!("student{}@example.com", i),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// 作业1的未提交提醒
+pub fn reminder() -> Reminder<'static> {
+    Reminder {
+        template: &TEMPLATE,
+        homework: "作业1",
+        deadline: "",
+        status: "未提交",
+        reasons: &NO_REASONS,
+        submit_to: "teacher@example.com",
+    }
+}
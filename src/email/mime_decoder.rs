@@ -0,0 +1,263 @@
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use encoding_rs::{Encoding, GB18030, UTF_8};
+use mailparse::{DispositionType, MailHeaderMap, ParsedMail};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::borrow::Cow;
+
+/// RFC 2047 编码字：`=?charset?B|Q?text?=`
+static ENCODED_WORD_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"=\?([^?\s]+)\?([BbQq])\?([^?\s]*)\?=").unwrap());
+
+/// 宽松的Base64解码器，不要求填充字符
+///
+/// 部分客户端会在多字节字符中间拆分编码字，拆分后的片段常常缺少填充
+static LENIENT_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// 获取邮件部分的附件文件名
+///
+/// 优先使用 Content-Disposition 中的 filename 参数，其次使用 Content-Type 中的 name 参数。
+/// 文件名支持以下编码方式：
+/// 1. RFC 2047 编码字（包括被错误地放在引号内或拆分到多个参数片段中的编码字）
+/// 2. RFC 2231 参数编码与续行（`filename*0*=GBK''...`）
+/// 3. 未经编码的原始 UTF-8 或 GBK 字节
+///
+/// # param
+/// * `part` - 邮件部分
+///
+/// # return
+/// * `Option<String>` - 解码后的文件名，非附件时为None
+pub fn attachment_filename(part: &ParsedMail) -> Option<String> {
+    let disposition = part
+        .headers
+        .get_first_header("Content-Disposition")
+        .map(|header| mailparse::parse_content_disposition(&decode_raw(header.get_value_raw())));
+
+    let filename = match &disposition {
+        Some(disposition) => match disposition.params.get("filename") {
+            Some(filename) => Some(filename.clone()),
+            None if disposition.disposition == DispositionType::Attachment => {
+                content_type_name(part)
+            }
+            None => None,
+        },
+        None => None,
+    }?;
+
+    let filename = decode_encoded_words(&filename);
+    Some(filename.trim().to_string()).filter(|name| !name.is_empty())
+}
+
+/// 获取 Content-Type 中的 name 参数
+fn content_type_name(part: &ParsedMail) -> Option<String> {
+    let header = part.headers.get_first_header("Content-Type")?;
+    mailparse::parse_content_type(&decode_raw(header.get_value_raw()))
+        .params
+        .get("name")
+        .cloned()
+}
+
+/// 解码邮件头字段的值
+///
+/// # param
+/// * `raw` - 邮件头字段的原始字节
+///
+/// # return
+/// * `String` - 解码后的字段值
+pub fn decode_header_value(raw: &[u8]) -> String {
+    decode_encoded_words(&decode_raw(raw))
+}
+
+/// 将邮件头的原始字节转换为字符串并展开折行
+///
+/// 合法的邮件头只包含ASCII或UTF-8（RFC 6532），但国内邮件客户端常直接写入GBK字节，
+/// 因此非UTF-8内容按GB18030（GBK的超集）解码
+fn decode_raw(raw: &[u8]) -> String {
+    let text: Cow<str> = match std::str::from_utf8(raw) {
+        Ok(text) => Cow::Borrowed(text),
+        Err(_) => GB18030.decode_without_bom_handling(raw).0,
+    };
+
+    text.replace(['\r', '\n'], "")
+}
+
+/// 解码字符串中的所有 RFC 2047 编码字
+///
+/// 相邻且字符集相同的编码字先合并字节再解码，以正确处理被拆分的多字节字符；
+/// 编码字之间的空白按 RFC 2047 忽略
+///
+/// # param
+/// * `text` - 可能包含编码字的字符串
+///
+/// # return
+/// * `String` - 解码后的字符串
+pub fn decode_encoded_words(text: &str) -> String {
+    let mut result = String::new();
+    let mut pending: Option<(&'static Encoding, Vec<u8>)> = None;
+    let mut last_end = 0;
+
+    for caps in ENCODED_WORD_REGEX.captures_iter(text) {
+        let word = caps.get(0).unwrap();
+        let gap = &text[last_end..word.start()];
+        let charset = caps.get(1).unwrap().as_str();
+        let encoding =
+            Encoding::for_label(charset.split('*').next().unwrap_or_default().as_bytes())
+                .unwrap_or(UTF_8);
+        let Some(bytes) = decode_word(caps.get(2).unwrap().as_str(), caps.get(3).unwrap().as_str())
+        else {
+            continue;
+        };
+
+        let adjacent = gap.trim().is_empty();
+        match &mut pending {
+            Some((pending_encoding, buffer)) if adjacent && *pending_encoding == encoding => {
+                buffer.extend_from_slice(&bytes);
+            }
+            _ => {
+                flush(&mut result, pending.take());
+                if !(adjacent && last_end > 0) {
+                    result.push_str(gap);
+                }
+                pending = Some((encoding, bytes));
+            }
+        }
+        last_end = word.end();
+    }
+
+    flush(&mut result, pending);
+    result.push_str(&text[last_end..]);
+    result
+}
+
+/// 解码单个编码字的内容
+fn decode_word(encoding: &str, text: &str) -> Option<Vec<u8>> {
+    if encoding.eq_ignore_ascii_case("b") {
+        return LENIENT_BASE64.decode(text).ok();
+    }
+
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut iter = text.bytes();
+    while let Some(byte) = iter.next() {
+        match byte {
+            b'_' => bytes.push(b' '),
+            b'=' => {
+                let hex = [iter.next()?, iter.next()?];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    Some(bytes)
+}
+
+/// 将缓冲的编码字字节按字符集解码后追加到结果中
+fn flush(result: &mut String, pending: Option<(&'static Encoding, Vec<u8>)>) {
+    if let Some((encoding, bytes)) = pending {
+        result.push_str(&encoding.decode_without_bom_handling(&bytes).0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filename_of(disposition: &[u8]) -> Option<String> {
+        let raw = [
+            b"Content-Type: application/octet-stream\r\nContent-Disposition: ".as_slice(),
+            disposition,
+            b"\r\n\r\ncontent",
+        ]
+        .concat();
+        attachment_filename(&mailparse::parse_mail(&raw).unwrap())
+    }
+
+    #[test]
+    fn should_decode_rfc2047_filename() {
+        assert_eq!(
+            filename_of(b"attachment; filename=\"=?UTF-8?B?5byg5LiJX+S9nOS4mi5kb2N4?=\"")
+                .as_deref(),
+            Some("张三_作业.docx")
+        );
+        assert_eq!(
+            filename_of(b"attachment; filename==?UTF-8?B?5byg5LiJX+S9nOS4mi5kb2N4?=").as_deref(),
+            Some("张三_作业.docx")
+        );
+        assert_eq!(
+            filename_of(b"attachment; filename=\"=?gb2312?B?1cXI/V/X99K1LmRvY3g=?=\"").as_deref(),
+            Some("张三_作业.docx")
+        );
+        assert_eq!(
+            filename_of(b"attachment; filename=\"=?GBK?Q?=D5=C5=C8=FD_=D7=F7=D2=B5.docx?=\"")
+                .as_deref(),
+            Some("张三 作业.docx")
+        );
+    }
+
+    #[test]
+    fn should_join_folded_and_split_encoded_words() {
+        assert_eq!(
+            filename_of(b"attachment;\r\n filename=\"=?UTF-8?B?5byg5LiJ?=\r\n =?UTF-8?B?X+S9nOS4mi5kb2N4?=\"")
+                .as_deref(),
+            Some("张三_作业.docx")
+        );
+        assert_eq!(
+            filename_of(
+                b"attachment; filename*0=\"=?UTF-8?B?5byg5LiJX+S9?=\"; filename*1=\"=?UTF-8?B?nOS4mi5kb2N4?=\""
+            )
+            .as_deref(),
+            Some("张三_作业.docx")
+        );
+    }
+
+    #[test]
+    fn should_decode_rfc2231_filename() {
+        assert_eq!(
+            filename_of(b"attachment; filename*=UTF-8''%E5%BC%A0%E4%B8%89_%E4%BD%9C%E4%B8%9A.docx")
+                .as_deref(),
+            Some("张三_作业.docx")
+        );
+        assert_eq!(
+            filename_of(
+                b"attachment; filename*0*=UTF-8''%E5%BC%A0%E4%B8%89; filename*1*=_%E4%BD%9C%E4%B8%9A.docx"
+            )
+            .as_deref(),
+            Some("张三_作业.docx")
+        );
+        assert_eq!(
+            filename_of(b"attachment; filename*=GBK''%D5%C5%C8%FD.docx").as_deref(),
+            Some("张三.docx")
+        );
+    }
+
+    #[test]
+    fn should_decode_raw_gbk_and_utf8_filename() {
+        let (gbk, _, _) = encoding_rs::GBK.encode("张三_作业.docx");
+        let disposition = [b"attachment; filename=\"".as_slice(), &gbk, b"\""].concat();
+
+        assert_eq!(filename_of(&disposition).as_deref(), Some("张三_作业.docx"));
+        assert_eq!(
+            filename_of("attachment; filename=\"李四_作业.pdf\"".as_bytes()).as_deref(),
+            Some("李四_作业.pdf")
+        );
+    }
+
+    #[test]
+    fn should_keep_text_around_encoded_words() {
+        assert_eq!(
+            decode_encoded_words("【作业3】 =?UTF-8?B?5byg5LiJ?= 提交"),
+            "【作业3】 张三 提交"
+        );
+        assert_eq!(decode_encoded_words("plain.txt"), "plain.txt");
+    }
+
+    #[test]
+    fn should_ignore_inline_parts_without_filename() {
+        assert_eq!(filename_of(b"inline"), None);
+    }
+}
@@ -1,3 +1,21 @@
+pub mod async_email_sender;
+pub mod dry_run_sender;
+pub mod email_receiver;
 pub mod email_sender;
+pub mod mime_decoder;
+pub mod rate_limiter;
+pub mod receive_state;
+pub mod retry;
+pub mod subject_filter;
+pub mod template;
 
+#[cfg(test)]
+pub(crate) mod fake_imap;
+#[cfg(test)]
+pub(crate) mod fake_smtp;
+#[cfg(test)]
+pub(crate) mod fixtures;
+
+pub use async_email_sender::AsyncEmailSender;
+pub use email_receiver::EmailReceiver;
 pub use email_sender::EmailSender;
@@ -0,0 +1,106 @@
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tokio::time::{self, Instant};
+
+/// 发送速率限制器
+///
+/// 将发送时刻均匀分布在每分钟内，避免短时间内突发大量邮件导致账号被邮件服务商限流或封禁。
+/// 同时支持异步与阻塞两种等待方式
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// 相邻两次发送的最小间隔，None表示不限制
+    interval: Option<Duration>,
+
+    /// 下一次允许发送的时刻
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    /// 创建每分钟最多允许 `per_minute` 次发送的限制器
+    ///
+    /// # param
+    /// * `per_minute` - 每分钟最多发送次数，0表示不限制
+    pub fn per_minute(per_minute: u32) -> Self {
+        let interval = (per_minute > 0).then(|| Duration::from_secs(60) / per_minute);
+        Self {
+            interval,
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    /// 异步等待直到允许下一次发送
+    pub async fn acquire(&self) {
+        if let Some(slot) = self.reserve_slot() {
+            time::sleep_until(slot).await;
+        }
+    }
+
+    /// 阻塞当前线程直到允许下一次发送
+    pub fn acquire_blocking(&self) {
+        if let Some(slot) = self.reserve_slot() {
+            thread::sleep(slot.saturating_duration_since(Instant::now()));
+        }
+    }
+
+    /// 预订下一个发送时刻，不限制时返回None
+    fn reserve_slot(&self) -> Option<Instant> {
+        let interval = self.interval?;
+
+        let mut next_slot = self.next_slot.lock().unwrap();
+        let now = Instant::now();
+        let slot = next_slot.map_or(now, |next| next.max(now));
+        *next_slot = Some(slot + interval);
+        Some(slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn should_space_out_acquisitions() {
+        // Given
+        let limiter = RateLimiter::per_minute(30);
+        let start = Instant::now();
+
+        // When
+        for _ in 0..4 {
+            limiter.acquire().await;
+        }
+
+        // Then
+        assert_eq!(start.elapsed(), Duration::from_secs(6));
+    }
+
+    #[test]
+    fn should_space_out_blocking_acquisitions() {
+        // Given
+        let limiter = RateLimiter::per_minute(1200);
+        let start = std::time::Instant::now();
+
+        // When
+        for _ in 0..3 {
+            limiter.acquire_blocking();
+        }
+
+        // Then
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_not_wait_when_unlimited() {
+        // Given
+        let limiter = RateLimiter::per_minute(0);
+        let start = Instant::now();
+
+        // When
+        for _ in 0..100 {
+            limiter.acquire().await;
+        }
+
+        // Then
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+}
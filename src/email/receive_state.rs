@@ -0,0 +1,251 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+/// 状态文件名，保存在作业下载目录下，检查提交时总是忽略该文件
+pub const STATE_FILE_NAME: &str = ".hsck_state.json";
+
+/// 最多记录的 Message-ID 数量，超出时丢弃 UID 最小的记录
+const MAX_MESSAGE_IDS: usize = 10_000;
+
+/// 邮件接收状态
+///
+/// 记录邮箱的 UIDVALIDITY 与已处理的最大 UID，使每次接收只拉取新邮件。
+/// 当 UIDVALIDITY 变化时，UID 不再可信，需要全量扫描并依靠 Message-ID 去重。
+/// 发件人不在学生名单中的邮件不算已处理，下次接收时会重新尝试
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiveState {
+    /// 上次接收时邮箱的 UIDVALIDITY
+    pub uid_validity: Option<u32>,
+
+    /// 已处理的最大 UID
+    pub last_uid: u32,
+
+    /// 已处理邮件的 Message-ID 及其 UID，UIDVALIDITY 变化后用于去重
+    #[serde(default)]
+    pub message_ids: BTreeMap<String, u32>,
+
+    /// 发件人不在学生名单中的邮件 UID，名单更新后重新处理
+    #[serde(default)]
+    pub unattributed: BTreeSet<u32>,
+}
+
+impl ReceiveState {
+    /// 从状态文件加载接收状态，文件不存在时返回初始状态
+    ///
+    /// # param
+    /// * `path` - 状态文件路径
+    ///
+    /// # return
+    /// * `Result<Self>` - 接收状态或错误
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("无法读取状态文件: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("无法解析状态文件: {}", path.display()))
+    }
+
+    /// 保存接收状态到状态文件
+    ///
+    /// # param
+    /// * `path` - 状态文件路径
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).context("无法序列化接收状态")?;
+        fs::write(path, content).with_context(|| format!("无法写入状态文件: {}", path.display()))
+    }
+
+    /// 根据邮箱当前的 UIDVALIDITY 决定本次需要搜索的邮件
+    ///
+    /// UIDVALIDITY 未变化时只搜索大于 `last_uid` 的邮件和未匹配到学生的邮件；
+    /// 否则重置 `last_uid` 并全量搜索，依靠已记录的 Message-ID 去重
+    ///
+    /// # param
+    /// * `uid_validity` - 邮箱当前的 UIDVALIDITY
+    ///
+    /// # return
+    /// * `String` - IMAP UID SEARCH 查询条件
+    pub fn search_query(&mut self, uid_validity: Option<u32>) -> String {
+        if uid_validity.is_some() && self.uid_validity == uid_validity {
+            let retries = self.unattributed.iter().map(|uid| format!("{},", uid));
+            return format!("UID {}{}:*", retries.collect::<String>(), self.last_uid + 1);
+        }
+
+        self.uid_validity = uid_validity;
+        self.last_uid = 0;
+        self.unattributed.clear();
+        "ALL".into()
+    }
+
+    /// 从搜索结果中取出本次需要处理的邮件
+    ///
+    /// 已从邮箱删除的未匹配邮件不再重试
+    ///
+    /// # param
+    /// * `found` - UID SEARCH 返回的邮件UID
+    ///
+    /// # return
+    /// * `Vec<u32>` - 新邮件和需要重试的邮件UID（升序）
+    pub fn pending_uids(&mut self, found: Vec<u32>) -> Vec<u32> {
+        self.unattributed.retain(|uid| found.contains(uid));
+        let mut uids: Vec<u32> = found
+            .into_iter()
+            .filter(|uid| *uid > self.last_uid || self.unattributed.contains(uid))
+            .collect();
+        uids.sort_unstable();
+        uids
+    }
+
+    /// 判断邮件是否已处理过
+    pub fn is_processed(&self, uid: u32, message_id: Option<&str>) -> bool {
+        (uid <= self.last_uid && !self.unattributed.contains(&uid))
+            || message_id.is_some_and(|id| self.message_ids.contains_key(id))
+    }
+
+    /// 记录已处理的邮件
+    ///
+    /// 已记录的 Message-ID 会更新为邮件当前的 UID，记录超过上限时丢弃 UID 最小的记录
+    pub fn mark_processed(&mut self, uid: u32, message_id: Option<&str>) {
+        self.last_uid = self.last_uid.max(uid);
+        self.unattributed.remove(&uid);
+        if let Some(id) = message_id {
+            self.message_ids.insert(id.to_string(), uid);
+        }
+
+        while self.message_ids.len() > MAX_MESSAGE_IDS {
+            let oldest = self
+                .message_ids
+                .iter()
+                .min_by_key(|(_, uid)| **uid)
+                .map(|(id, _)| id.clone());
+            if let Some(id) = oldest {
+                self.message_ids.remove(&id);
+            }
+        }
+    }
+
+    /// 记录发件人不在学生名单中的邮件，下次接收时重新处理
+    pub fn mark_unattributed(&mut self, uid: u32) {
+        self.last_uid = self.last_uid.max(uid);
+        self.unattributed.insert(uid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn should_search_incrementally_when_uid_validity_unchanged() {
+        // Given
+        let mut state = ReceiveState {
+            uid_validity: Some(7),
+            last_uid: 42,
+            ..Default::default()
+        };
+
+        // When
+        let query = state.search_query(Some(7));
+
+        // Then
+        assert_eq!(query, "UID 43:*");
+        assert_eq!(state.last_uid, 42);
+    }
+
+    #[test]
+    fn should_retry_unattributed_mail_and_keep_message_ids() {
+        // Given
+        let mut state = ReceiveState {
+            uid_validity: Some(7),
+            ..Default::default()
+        };
+        state.mark_processed(3, Some("<a@example.com>"));
+        state.mark_unattributed(5);
+        state.mark_unattributed(8);
+        state.mark_processed(9, Some("<b@example.com>"));
+
+        // When
+        let query = state.search_query(Some(7));
+        let pending = state.pending_uids(vec![3, 5, 9, 10]);
+
+        // Then
+        assert_eq!(query, "UID 5,8,10:*");
+        assert_eq!(pending, [5, 10]);
+        assert_eq!(state.unattributed, BTreeSet::from([5]));
+        assert_eq!(
+            state.message_ids,
+            BTreeMap::from([
+                ("<a@example.com>".to_string(), 3),
+                ("<b@example.com>".to_string(), 9),
+            ])
+        );
+        assert!(state.is_processed(3, None));
+        assert!(!state.is_processed(5, None));
+    }
+
+    #[test]
+    fn should_rescan_when_uid_validity_changed() {
+        // Given
+        let mut state = ReceiveState {
+            uid_validity: Some(7),
+            last_uid: 42,
+            message_ids: BTreeMap::from([("<a@example.com>".to_string(), 42)]),
+            unattributed: BTreeSet::from([40]),
+        };
+
+        // When
+        let query = state.search_query(Some(8));
+
+        // Then
+        assert_eq!(query, "ALL");
+        assert_eq!(state.uid_validity, Some(8));
+        assert_eq!(state.last_uid, 0);
+        assert!(state.unattributed.is_empty());
+        assert!(state.is_processed(1, Some("<a@example.com>")));
+        assert!(!state.is_processed(1, Some("<b@example.com>")));
+    }
+
+    #[test]
+    fn should_round_trip_state_file() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let path = tmp_dir.path().join(STATE_FILE_NAME);
+        let mut state = ReceiveState::load(&path)?;
+        state.search_query(Some(3));
+        state.mark_processed(5, Some("<a@example.com>"));
+
+        // When
+        state.save(&path)?;
+        let loaded = ReceiveState::load(&path)?;
+
+        // Then
+        assert_eq!(loaded, state);
+        assert_eq!(loaded.last_uid, 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_drop_oldest_message_ids_over_limit() {
+        // Given
+        let mut state = ReceiveState::default();
+        for uid in 1..=MAX_MESSAGE_IDS as u32 {
+            state.mark_processed(uid, Some(&format!("<{}@example.com>", uid)));
+        }
+
+        // When
+        state.mark_processed(20_000, Some("<new@example.com>"));
+
+        // Then
+        assert_eq!(state.message_ids.len(), MAX_MESSAGE_IDS);
+        assert!(state.is_processed(0, Some("<new@example.com>")));
+        assert!(!state.message_ids.contains_key("<1@example.com>"));
+        assert!(state.message_ids.contains_key("<2@example.com>"));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::config::app_config::SmtpConfig;
+use anyhow::Result;
+use lettre::transport::smtp::Error as SmtpError;
+use log::warn;
+use std::future::Future;
+use std::thread;
+use std::time::Duration;
+
+/// 发送失败重试策略
+///
+/// 仅对暂时性错误（4xx响应、超时、网络或连接中断）进行重试，每次重试前的等待时间翻倍。
+/// 永久性错误（5xx响应、地址无效、TLS或认证错误等）立即返回
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// 最多尝试次数（包括第一次发送）
+    max_attempts: u32,
+
+    /// 第一次重试前的等待时间
+    initial_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// 根据SMTP配置创建重试策略
+    pub fn from_config(smtp_config: &SmtpConfig) -> Self {
+        Self {
+            max_attempts: smtp_config.retry_attempts.max(1),
+            initial_backoff: Duration::from_millis(smtp_config.retry_backoff_ms),
+        }
+    }
+
+    /// 执行操作，遇到暂时性错误时在阻塞等待后重试
+    ///
+    /// # param
+    /// * `operation` - 要执行的操作
+    ///
+    /// # return
+    /// * `Result<T>` - 最后一次尝试的结果
+    pub fn run_blocking<T>(&self, mut operation: impl FnMut() -> Result<T>) -> Result<T> {
+        let mut attempt = 1;
+        loop {
+            match operation() {
+                Err(e) if self.should_retry(attempt, &e) => {
+                    thread::sleep(self.backoff(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// 执行异步操作，遇到暂时性错误时在异步等待后重试
+    ///
+    /// # param
+    /// * `operation` - 生成每次尝试的异步操作
+    ///
+    /// # return
+    /// * `Result<T>` - 最后一次尝试的结果
+    pub async fn run<T, F, Fut>(&self, mut operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(e) if self.should_retry(attempt, &e) => {
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// 判断第 `attempt` 次尝试失败后是否需要重试
+    fn should_retry(&self, attempt: u32, error: &anyhow::Error) -> bool {
+        if attempt >= self.max_attempts || !is_transient(error) {
+            return false;
+        }
+
+        warn!(
+            "第{}次发送遇到暂时性错误，{:?}后重试: {:#}",
+            attempt,
+            self.backoff(attempt),
+            error
+        );
+        true
+    }
+
+    /// 第 `attempt` 次尝试失败后的等待时间
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+    }
+}
+
+/// 判断错误是否为可重试的暂时性SMTP错误
+///
+/// 4xx响应（如灰名单、服务器繁忙）、超时以及网络或连接中断视为暂时性错误；
+/// 5xx响应、客户端错误、TLS错误、响应解析错误以及非SMTP错误（如邮件构建失败）视为永久性错误
+///
+/// # param
+/// * `error` - 发送错误
+///
+/// # return
+/// * `bool` - 是否为暂时性错误
+pub fn is_transient(error: &anyhow::Error) -> bool {
+    let Some(smtp_error) = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<SmtpError>())
+    else {
+        return false;
+    };
+
+    if smtp_error.is_transient() || smtp_error.is_timeout() {
+        return true;
+    }
+
+    !(smtp_error.is_permanent()
+        || smtp_error.is_client()
+        || smtp_error.is_tls()
+        || smtp_error.is_response()
+        || smtp_error.is_transport_shutdown())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(1),
+        }
+    }
+
+    #[test]
+    fn should_double_backoff_between_attempts() {
+        // Given
+        let policy = RetryPolicy {
+            max_attempts: 4,
+            initial_backoff: Duration::from_secs(1),
+        };
+
+        // When & Then
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn should_not_retry_non_smtp_errors() {
+        // Given
+        let mut attempts = 0;
+
+        // When
+        let result: Result<()> = policy(3).run_blocking(|| {
+            attempts += 1;
+            Err(anyhow!("无效的收件人地址"))
+        });
+
+        // Then
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+}
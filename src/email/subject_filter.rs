@@ -0,0 +1,142 @@
+use crate::config::app_config::{Homework, ImapConfig};
+use anyhow::{Context, Result};
+use regex::Regex;
+
+/// 正则表达式模式的前缀，没有该前缀的模式按通配符处理
+pub const REGEX_PREFIX: &str = "regex:";
+
+/// 模式中代表作业名称的占位符
+pub const HOMEWORK_PLACEHOLDER: &str = "{homework}";
+
+/// 邮件主题过滤器
+///
+/// 根据作业名称筛选提交邮件，使同一个邮箱可以同时收取多份作业
+#[derive(Debug)]
+pub struct SubjectFilter {
+    /// 编译后的主题匹配规则
+    regex: Regex,
+}
+
+impl SubjectFilter {
+    /// 根据IMAP配置为指定作业创建主题过滤器
+    ///
+    /// 优先使用作业定义中的 `subject_pattern`，否则使用通用的 `imap_config.subject_pattern`
+    ///
+    /// # param
+    /// * `imap_config` - IMAP配置
+    /// * `homework` - 作业定义
+    ///
+    /// # return
+    /// * `Result<Self>` - 主题过滤器或模式错误
+    pub fn for_homework(imap_config: &ImapConfig, homework: &Homework) -> Result<Self> {
+        let pattern = Some(homework.subject_pattern.as_str())
+            .filter(|pattern| !pattern.is_empty())
+            .unwrap_or(&imap_config.subject_pattern);
+
+        Self::from_pattern(pattern, homework.display_name())
+    }
+
+    /// 根据模式字符串创建主题过滤器
+    ///
+    /// 模式中的 `{homework}` 会被替换为作业名称。以 `regex:` 开头的模式按正则表达式处理，
+    /// 否则按通配符处理：`*` 匹配任意字符序列，`?` 匹配单个字符，其余字符按字面匹配
+    ///
+    /// # param
+    /// * `pattern` - 模式字符串
+    /// * `homework_name` - 作业名称
+    ///
+    /// # return
+    /// * `Result<Self>` - 主题过滤器或模式错误
+    pub fn from_pattern(pattern: &str, homework_name: &str) -> Result<Self> {
+        let regex = match pattern.strip_prefix(REGEX_PREFIX) {
+            Some(regex) => regex.replace(HOMEWORK_PLACEHOLDER, &regex::escape(homework_name)),
+            None => glob_to_regex(&pattern.replace(HOMEWORK_PLACEHOLDER, homework_name)),
+        };
+
+        let regex =
+            Regex::new(&regex).with_context(|| format!("无效的邮件主题模式: {}", pattern))?;
+        Ok(Self { regex })
+    }
+
+    /// 判断邮件主题是否匹配
+    pub fn matches(&self, subject: &str) -> bool {
+        self.regex.is_match(subject.trim())
+    }
+}
+
+/// 将通配符模式转换为完整匹配的正则表达式
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_glob_pattern_with_homework_placeholder() -> Result<()> {
+        // Given
+        let filter = SubjectFilter::from_pattern("【{homework}】*", "作业3")?;
+
+        // When & Then
+        assert!(filter.matches("【作业3】张三 2023001"));
+        assert!(!filter.matches("【作业4】张三 2023001"));
+        assert!(!filter.matches("Re: 【作业3】张三"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_treat_regex_metacharacters_in_glob_literally() -> Result<()> {
+        // Given
+        let filter = SubjectFilter::from_pattern("[{homework}] (*)", "hw3")?;
+
+        // When & Then
+        assert!(filter.matches("[hw3] (张三)"));
+        assert!(!filter.matches("h (张三)"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_match_regex_pattern() -> Result<()> {
+        // Given
+        let filter = SubjectFilter::from_pattern(r"regex:(?i)^(hw|作业)\s*3\b", "作业3")?;
+
+        // When & Then
+        assert!(filter.matches("HW 3 - 张三"));
+        assert!(filter.matches("作业3 张三"));
+        assert!(!filter.matches("作业33 张三"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_not_match_longer_homework_name_with_default_pattern() -> Result<()> {
+        // Given
+        let imap_config: ImapConfig = serde_json::from_str("{}")?;
+        let filter = SubjectFilter::for_homework(&imap_config, &Homework::named("作业1"))?;
+
+        // When & Then
+        assert!(filter.matches("【作业1】张三 2023001"));
+        assert!(filter.matches("Re: 【作业1】张三"));
+        assert!(!filter.matches("【作业10】张三 2023001"));
+        assert!(!filter.matches("作业1 张三"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_invalid_regex() {
+        assert!(SubjectFilter::from_pattern("regex:(", "作业3").is_err());
+    }
+}
@@ -0,0 +1,337 @@
+use crate::config::app_config::TemplateConfig;
+use crate::config::stu::Stu;
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// 模板占位符：`{{name}}`，名称两侧允许空白
+static PLACEHOLDER_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{\s*([A-Za-z_][\w.]*)\s*\}\}").unwrap());
+
+/// 模板支持的占位符
+pub const PLACEHOLDERS: [&str; 10] = [
+    "student.name",
+    "student.email",
+    "student.id",
+    "student.class",
+    "student.group",
+    "homework",
+    "deadline",
+    "status",
+    "reason",
+    "submit_to",
+];
+
+/// 提醒邮件模板
+///
+/// 由邮件主题、纯文本正文与HTML正文三部分组成，支持 `PLACEHOLDERS` 中列出的占位符
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReminderTemplate {
+    /// 邮件主题模板
+    subject: String,
+
+    /// 纯文本正文模板
+    text: String,
+
+    /// HTML正文模板
+    html: String,
+}
+
+impl ReminderTemplate {
+    /// 根据模板字符串创建模板，并检查其中的占位符
+    ///
+    /// # param
+    /// * `subject` - 邮件主题模板
+    /// * `text` - 纯文本正文模板
+    /// * `html` - HTML正文模板
+    ///
+    /// # return
+    /// * `Result<Self>` - 模板或未知占位符错误
+    pub fn new(subject: &str, text: &str, html: &str) -> Result<Self> {
+        for (part, template) in [("主题", subject), ("纯文本正文", text), ("HTML正文", html)]
+        {
+            check_placeholders(template).with_context(|| format!("邮件{}模板无效", part))?;
+        }
+
+        Ok(Self {
+            subject: subject.to_string(),
+            text: text.to_string(),
+            html: html.to_string(),
+        })
+    }
+
+    /// 从配置目录加载模板
+    ///
+    /// 模板文件路径为相对路径时相对于配置目录解析
+    ///
+    /// # param
+    /// * `template_config` - 模板配置
+    /// * `config_dir` - 配置目录
+    ///
+    /// # return
+    /// * `Result<Self>` - 模板或读取错误
+    pub fn load(template_config: &TemplateConfig, config_dir: &Path) -> Result<Self> {
+        Self::read(
+            &template_config.subject,
+            &config_dir.join(&template_config.text_file),
+            &config_dir.join(&template_config.html_file),
+        )
+    }
+
+    /// 从配置目录加载无效提交提醒模板
+    ///
+    /// # param
+    /// * `template_config` - 模板配置
+    /// * `config_dir` - 配置目录
+    ///
+    /// # return
+    /// * `Result<Self>` - 模板或读取错误
+    pub fn load_invalid(template_config: &TemplateConfig, config_dir: &Path) -> Result<Self> {
+        Self::read(
+            &template_config.invalid_subject,
+            &config_dir.join(&template_config.invalid_text_file),
+            &config_dir.join(&template_config.invalid_html_file),
+        )
+    }
+
+    /// 读取正文模板文件并创建模板
+    fn read(subject: &str, text_file: &Path, html_file: &Path) -> Result<Self> {
+        let read = |path: &Path| {
+            fs::read_to_string(path)
+                .with_context(|| format!("无法读取邮件模板: {}", path.display()))
+        };
+
+        Self::new(subject, &read(text_file)?, &read(html_file)?)
+    }
+}
+
+/// 一次提醒任务中所有学生共用的模板与变量
+#[derive(Debug, Clone, Copy)]
+pub struct Reminder<'a> {
+    /// 提醒邮件模板
+    pub template: &'a ReminderTemplate,
+
+    /// 作业名称
+    pub homework: &'a str,
+
+    /// 截止时间，未指定时为空
+    pub deadline: &'a str,
+
+    /// 提交状态，如 `未提交`、`逾期 1天3小时未提交`
+    pub status: &'a str,
+
+    /// 每名学生的提交无效原因，以邮箱为键，用于无效提交提醒
+    pub reasons: &'a HashMap<String, String>,
+
+    /// 作业提交地址
+    pub submit_to: &'a str,
+}
+
+impl Reminder<'_> {
+    /// 为指定学生渲染提醒邮件
+    ///
+    /// HTML正文中的变量值会进行转义，防止学生姓名等内容破坏邮件结构
+    ///
+    /// # param
+    /// * `student` - 收件学生
+    ///
+    /// # return
+    /// * `Notification` - 渲染后的邮件内容
+    pub fn render(&self, student: &Stu) -> Notification {
+        let group = student.group.map(|group| group.to_string());
+        let value = |name: &str| match name {
+            "student.name" => student.name.as_str(),
+            "student.email" => student.email.as_str(),
+            "student.id" => student.id.as_deref().unwrap_or_default(),
+            "student.class" => student.class.as_deref().unwrap_or_default(),
+            "student.group" => group.as_deref().unwrap_or_default(),
+            "homework" => self.homework,
+            "deadline" => self.deadline,
+            "status" => self.status,
+            "reason" => self
+                .reasons
+                .get(&student.email)
+                .map(String::as_str)
+                .unwrap_or_default(),
+            "submit_to" => self.submit_to,
+            _ => unreachable!("模板创建时已检查占位符"),
+        };
+
+        Notification {
+            subject: render(&self.template.subject, |name| value(name).to_string()),
+            text: render(&self.template.text, |name| value(name).to_string()),
+            html: render(&self.template.html, |name| escape_html(value(name))),
+        }
+    }
+}
+
+/// 渲染后的提醒邮件内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    /// 邮件主题
+    pub subject: String,
+
+    /// 纯文本内容
+    pub text: String,
+
+    /// HTML内容
+    pub html: String,
+}
+
+/// 检查模板中的占位符是否都受支持
+fn check_placeholders(template: &str) -> Result<()> {
+    for caps in PLACEHOLDER_REGEX.captures_iter(template) {
+        let name = &caps[1];
+        if !PLACEHOLDERS.contains(&name) {
+            bail!(
+                "未知的占位符 {{{{{}}}}}，可用的占位符: {}",
+                name,
+                PLACEHOLDERS.join(", ")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// 将模板中的占位符替换为对应的值
+fn render(template: &str, value: impl Fn(&str) -> String) -> String {
+    PLACEHOLDER_REGEX
+        .replace_all(template, |caps: &Captures| value(&caps[1]))
+        .into_owned()
+}
+
+/// 转义HTML特殊字符
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn student() -> Stu {
+        Stu {
+            name: "张三".into(),
+            email: "zhangsan@example.com".into(),
+            group: Some(3),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_render_all_placeholders() -> Result<()> {
+        // Given
+        let template = ReminderTemplate::new(
+            "【{{homework}}】未提交提醒",
+            "{{ student.name }}同学，请在{{deadline}}前将<{{homework}}>发送到{{submit_to}}（{{status}}）",
+            "<p>{{student.name}}（{{student.email}}，第{{student.group}}组）{{reason}}</p>",
+        )?;
+        let reasons = HashMap::from([(
+            "zhangsan@example.com".to_string(),
+            "报告.pdf: 文件为空".to_string(),
+        )]);
+        let reminder = Reminder {
+            template: &template,
+            homework: "作业3",
+            deadline: "2024-10-01 23:59",
+            status: "逾期 3小时未提交",
+            reasons: &reasons,
+            submit_to: "ta@example.com",
+        };
+
+        // When
+        let notification = reminder.render(&student());
+
+        // Then
+        assert_eq!(notification.subject, "【作业3】未提交提醒");
+        assert_eq!(
+            notification.text,
+            "张三同学，请在2024-10-01 23:59前将<作业3>发送到ta@example.com（逾期 3小时未提交）"
+        );
+        assert_eq!(
+            notification.html,
+            "<p>张三（zhangsan@example.com，第3组）报告.pdf: 文件为空</p>"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_escape_values_in_html() -> Result<()> {
+        // Given
+        let template =
+            ReminderTemplate::new("{{homework}}", "{{homework}}", "<b>{{homework}}</b>")?;
+        let reminder = Reminder {
+            template: &template,
+            homework: "<C&C++>",
+            deadline: "",
+            status: "",
+            reasons: &HashMap::new(),
+            submit_to: "",
+        };
+
+        // When
+        let notification = reminder.render(&student());
+
+        // Then
+        assert_eq!(notification.subject, "<C&C++>");
+        assert_eq!(notification.html, "<b>&lt;C&amp;C++&gt;</b>");
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_unknown_placeholder() {
+        let result = ReminderTemplate::new("提醒", "{{student.phone}}", "");
+
+        assert!(result.is_err_and(|e| format!("{:#}", e).contains("student.phone")));
+    }
+
+    #[test]
+    fn should_load_templates_relative_to_config_dir() -> Result<()> {
+        // Given
+        let config_dir = tempdir()?;
+        fs::create_dir(config_dir.path().join("templates"))?;
+        fs::write(config_dir.path().join("templates/a.txt"), "{{homework}}")?;
+        fs::write(
+            config_dir.path().join("templates/a.html"),
+            "<p>{{homework}}</p>",
+        )?;
+        let template_config = TemplateConfig {
+            subject: "提醒".into(),
+            text_file: "templates/a.txt".into(),
+            html_file: "templates/a.html".into(),
+            invalid_text_file: "templates/a.html".into(),
+            invalid_html_file: "templates/a.txt".into(),
+            ..Default::default()
+        };
+
+        // When
+        let template = ReminderTemplate::load(&template_config, config_dir.path())?;
+        let invalid = ReminderTemplate::load_invalid(&template_config, config_dir.path())?;
+
+        // Then
+        assert_eq!(template.text, "{{homework}}");
+        assert_eq!(template.html, "<p>{{homework}}</p>");
+        assert_eq!(invalid.subject, "作业提交无效提醒：{{homework}}");
+        assert_eq!(invalid.text, "<p>{{homework}}</p>");
+
+        Ok(())
+    }
+}
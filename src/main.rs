@@ -4,40 +4,94 @@ mod email;
 mod logger;
 mod utils;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use chrono::Local;
 use clap::Parser;
-use cli::Cli;
-use config::{app_config::AppConfig, loader, stu::Stu};
-use email::EmailSender;
+use cli::{Cli, Command, ConfigAction};
+use config::{
+    app_config::{AppConfig, Homework, HomeworkKind, SmtpConfig},
+    loader,
+    stu::Stu,
+    validation::ConfigValidationError,
+};
+use email::dry_run_sender::DryRunSender;
+use email::template::{Reminder, ReminderTemplate};
+use email::{AsyncEmailSender, EmailReceiver, EmailSender};
 use log::{error, info, warn};
+use std::collections::HashMap;
 use std::env;
+use std::path::{Path, PathBuf};
+use utils::archive::{self, ArchiveKind};
+use utils::content::{format_size, ContentRules};
+use utils::deadline::{format_duration, Deadline, SubmissionStatus};
+use utils::filename_pattern::FilenamePattern;
+use utils::scanner::ScanOptions;
+use utils::{FileIssue, InvalidSubmission, Submitted};
 
 fn main() -> Result<()> {
     // 解析命令行参数
     let cmd_args: Cli = Cli::parse();
     // 设置环境变量
     set_env(&cmd_args);
+    // 执行配置管理子命令
+    if let Some(Command::Config { action }) = &cmd_args.command {
+        return run_config_command(action, &cmd_args);
+    }
     // 加载应用配置
     let app_config = load_app_config(&cmd_args);
 
     // 初始化日志系统
     logger::init_with_config(&app_config.log_config)?;
+    print_config_warnings(&app_config.warnings);
 
-    // 检查未提交学生
-    let missing = utils::check_missing(&app_config.stu_config, cmd_args.check_dir.as_deref())?;
-    if missing.is_empty() {
-        println!("🎉 所有学生均已提交作业");
-        return Ok(());
+    // 确定要检查的作业
+    let homework = resolve_homework(&cmd_args, &app_config)?;
+    if cmd_args.send && homework.is_none() {
+        bail!("发送邮件时必须指定作业：使用 -n <作业名称> 或 check <作业>");
     }
 
-    // 打印未提交学生名单
-    let missing_names: Vec<String> = missing.iter().map(|stu| stu.name.to_string()).collect();
-    println!("❌ 未提交学生名单：\n{}", missing_names.join("\n"));
+    // 接收邮件，可选
+    let check_dir = receive_email(&cmd_args, &app_config, homework.as_ref())?;
 
-    // 发送邮件，可选
-    send_email(&cmd_args, &missing, &app_config)?;
+    // 检查未提交学生或小组
+    let deadline = match cmd_args.deadline {
+        Some(deadline) => Some(deadline),
+        None => homework
+            .as_ref()
+            .map(Homework::deadline)
+            .transpose()?
+            .flatten(),
+    };
+    let group_cnt = match &homework {
+        _ if cmd_args.group_cnt > 0 => cmd_args.group_cnt,
+        Some(homework) if homework.kind == HomeworkKind::Group => homework.groups,
+        _ => 0,
+    };
+    let (missing, invalid) = if group_cnt > 0 {
+        let missing = check_groups(&app_config, check_dir.as_deref(), group_cnt)?;
+        (missing, Vec::new())
+    } else {
+        check_students(
+            &cmd_args,
+            &app_config,
+            homework.as_ref(),
+            check_dir.as_deref(),
+            deadline,
+        )?
+    };
+    if missing.is_empty() && invalid.is_empty() {
+        return Ok(());
+    }
 
-    // TODO 接收邮件，可选
+    // 发送邮件，可选
+    send_email(
+        &cmd_args,
+        &missing,
+        &invalid,
+        &app_config,
+        homework.as_ref(),
+        deadline,
+    )?;
 
     Ok(())
 }
@@ -54,6 +108,42 @@ fn set_env(cmd_args: &Cli) {
     }
 }
 
+/// 执行配置管理子命令
+fn run_config_command(action: &ConfigAction, cmd_args: &Cli) -> Result<()> {
+    let config_dir = &cmd_args.config_dir;
+    match action {
+        ConfigAction::Init { force } => {
+            let stdin = std::io::stdin();
+            let written = config::command::init(
+                config_dir,
+                *force,
+                &mut stdin.lock(),
+                &mut std::io::stdout(),
+            )?;
+            println!("✅ 已生成配置文件：");
+            for path in written {
+                println!("  {}", path.display());
+            }
+        }
+        ConfigAction::Show => {
+            config::command::show(config_dir, &cmd_args.env, &mut std::io::stdout())?
+        }
+        ConfigAction::Validate => {
+            let warnings = match config::command::validate(config_dir, &cmd_args.env) {
+                Ok(warnings) => warnings,
+                Err(e) => {
+                    eprintln!("❌ {:#}", e);
+                    std::process::exit(1);
+                }
+            };
+            print_config_warnings(&warnings);
+            println!("✅ 配置有效（环境: {}）", cmd_args.env);
+        }
+    }
+
+    Ok(())
+}
+
 /// 加载应用配置
 fn load_app_config(cmd_args: &Cli) -> AppConfig {
     match loader::load_config() {
@@ -62,7 +152,9 @@ fn load_app_config(cmd_args: &Cli) -> AppConfig {
             config
         }
         Err(e) => {
+            // 此时日志尚未初始化，需要直接输出到终端
             error!("配置加载失败: {}", e);
+            eprintln!("❌ 配置加载失败: {}", e);
             eprintln!(
                 "请确保配置文件存在于 {} 目录中",
                 cmd_args.config_dir.display()
@@ -72,20 +164,380 @@ fn load_app_config(cmd_args: &Cli) -> AppConfig {
     }
 }
 
+/// 确定要检查的作业
+///
+/// `check` 子命令指定的作业必须在配置中定义；`-n` 指定的作业未定义时使用默认设置
+fn resolve_homework(cmd_args: &Cli, app_config: &AppConfig) -> Result<Option<Homework>> {
+    match (&cmd_args.command, &cmd_args.homework_name) {
+        (Some(Command::Check { homework }), _) => {
+            let ids: Vec<&str> = app_config.homework.iter().map(|h| h.id.as_str()).collect();
+            let homework = app_config.find_homework(homework).with_context(|| {
+                format!(
+                    "配置中没有定义作业 {}，已定义的作业: {}",
+                    homework,
+                    ids.join(", ")
+                )
+            })?;
+            Ok(Some(homework.clone()))
+        }
+        (_, Some(name)) => Ok(Some(
+            app_config
+                .find_homework(name)
+                .cloned()
+                .unwrap_or_else(|| Homework::named(name)),
+        )),
+        _ => Ok(None),
+    }
+}
+
+/// 打印配置警告
+fn print_config_warnings(warnings: &[ConfigValidationError]) {
+    for warning in warnings {
+        warn!("配置警告: {}", warning);
+        println!("⚠️ {}\n   hint: {}", warning, warning.suggestion());
+    }
+}
+
+/// 检查未提交作业的学生
+///
+/// 指定了截止时间时，同时列出迟交的学生及迟交时长；可选地列出或整理已提交学生的文件。
+/// 返回需要提醒的未提交学生与无效提交
+fn check_students(
+    cmd_args: &Cli,
+    app_config: &AppConfig,
+    homework: Option<&Homework>,
+    check_dir: Option<&Path>,
+    deadline: Option<Deadline>,
+) -> Result<(Vec<Stu>, Vec<InvalidSubmission>)> {
+    let pattern = FilenamePattern::for_homework(&app_config.check_config, homework)?;
+    let report = utils::check_submissions(
+        &app_config.stu_config,
+        check_dir,
+        pattern.as_ref(),
+        &ContentRules::for_homework(homework)?,
+        &ScanOptions::from_config(&app_config.check_config)?,
+    )?;
+    print_unmatched(
+        &report.unmatched,
+        "⚠️ 以下文件不符合命名规范或无法对应到学生：",
+    );
+
+    if let Some(deadline) = deadline {
+        let late: Vec<String> = report
+            .submitted
+            .iter()
+            .filter_map(|submitted| match deadline.status(submitted.submitted_at) {
+                status @ SubmissionStatus::Late(_) => {
+                    Some(format!("{}: {}", submitted.student.label(), status))
+                }
+                _ => None,
+            })
+            .collect();
+        println!("⏰ 截止时间：{}", deadline);
+        println!("✅ 按时提交 {} 人", report.submitted.len() - late.len());
+        if !late.is_empty() {
+            println!("⚠️ 迟交学生名单：\n{}", late.join("\n"));
+        }
+    }
+
+    print_unmatched(&report.unchecked, "❔ 以下提交无法完整检查，计入已提交：");
+
+    let root = check_dir.unwrap_or_else(|| Path::new("."));
+    if cmd_args.list {
+        print_submissions(root, &report.submitted);
+    }
+    if let Some(extract_dir) = &cmd_args.extract_dir {
+        extract_submissions(root, &report.submitted, extract_dir);
+    }
+
+    if !report.invalid.is_empty() {
+        println!("⚠️ 提交无效的学生名单：");
+        for invalid in &report.invalid {
+            println!("{}", invalid.student.label());
+            for file in &invalid.files {
+                println!("  {}: {}", file.name, file.reason);
+            }
+        }
+    }
+
+    if !report.missing.is_empty() {
+        let missing_names: Vec<String> = report.missing.iter().map(Stu::label).collect();
+        println!("❌ 未提交学生名单：\n{}", missing_names.join("\n"));
+    } else if report.invalid.is_empty() {
+        println!("🎉 所有学生均已提交作业");
+    }
+
+    Ok((report.missing, report.invalid))
+}
+
+/// 列出已提交学生的文件，压缩包和文件夹展开列出其中的文件
+fn print_submissions(root: &Path, submitted: &[Submitted]) {
+    println!("📂 提交内容：");
+    for submitted in submitted {
+        println!("{}", submitted.student.label());
+        for file in &submitted.files {
+            let path = root.join(file);
+            println!("  {}", file.display());
+            let contents = match ArchiveKind::detect(&path) {
+                _ if path.is_dir() => archive::list_dir(&path).map(Some),
+                Some(kind) => archive::list(&path, kind),
+                None => continue,
+            };
+            match contents {
+                Ok(Some(entries)) => {
+                    for entry in entries {
+                        println!("    {} ({})", entry.path, format_size(entry.size));
+                    }
+                }
+                Ok(None) => println!("    （不支持查看该格式压缩包中的文件）"),
+                Err(e) => println!("    ⚠️ {:#}", e),
+            }
+        }
+    }
+}
+
+/// 将已提交学生的文件整理到 `<extract_dir>/<学号_姓名>/`，压缩包解压到该目录
+fn extract_submissions(root: &Path, submitted: &[Submitted], extract_dir: &Path) {
+    let mut failed = Vec::new();
+    for submitted in submitted {
+        let label = submitted.student.label();
+        if let Err(e) =
+            archive::collect_submission(root, &submitted.files, &extract_dir.join(&label))
+        {
+            warn!("整理 {} 的提交失败: {:#}", label, e);
+            failed.push(format!("{}: {:#}", label, e));
+        }
+    }
+
+    println!(
+        "📦 已将 {} 名学生的提交整理到 {}",
+        submitted.len() - failed.len(),
+        extract_dir.display()
+    );
+    if !failed.is_empty() {
+        println!("❌ 以下学生的提交整理失败：\n{}", failed.join("\n"));
+    }
+}
+
+/// 检查未提交作业的小组
+///
+/// 返回需要提醒的学生，即未提交小组的全部成员
+fn check_groups(
+    app_config: &AppConfig,
+    check_dir: Option<&Path>,
+    group_cnt: u32,
+) -> Result<Vec<Stu>> {
+    let report = utils::group::check_groups(
+        &app_config.stu_config,
+        check_dir,
+        group_cnt,
+        &ScanOptions::from_config(&app_config.check_config)?,
+    )?;
+    print_unmatched(&report.unmatched, "⚠️ 以下文件无法对应到小组：");
+
+    if report.missing.is_empty() {
+        println!("🎉 所有小组均已提交作业");
+        return Ok(Vec::new());
+    }
+
+    println!("❌ 未提交小组名单：");
+    for group in &report.missing {
+        let members: Vec<String> = group.members.iter().map(Stu::label).collect();
+        match members.is_empty() {
+            true => println!("第{}组（学生名单中没有该组成员）", group.number),
+            false => println!("第{}组：{}", group.number, members.join("、")),
+        }
+    }
+
+    Ok(report
+        .missing
+        .into_iter()
+        .flat_map(|group| group.members)
+        .collect())
+}
+
+/// 打印无法对应到学生或小组的文件
+fn print_unmatched(unmatched: &[FileIssue], title: &str) {
+    if !unmatched.is_empty() {
+        println!("{}", title);
+        for file in unmatched {
+            println!("  {}: {}", file.name, file.reason);
+        }
+    }
+}
+
+/// 接收邮件
+///
+/// 接收邮件时附件下载到作业下载目录（作业定义中的目录，或IMAP输出目录下的作业目录）。
+/// 返回需要检查的目录：优先使用 `-d` 指定的目录，其次为作业定义中的目录，接收邮件时默认为作业下载目录
+fn receive_email(
+    cmd_args: &Cli,
+    app_config: &AppConfig,
+    homework: Option<&Homework>,
+) -> Result<Option<PathBuf>> {
+    if !cmd_args.resv {
+        let check_dir = homework.and_then(Homework::submit_dir);
+        return Ok(cmd_args.check_dir.clone().or(check_dir));
+    }
+
+    let receiver = EmailReceiver::new(&app_config.imap_config, &app_config.stu_config.list);
+    let summary = receiver.receive(homework)?;
+    println!("📥 共下载 {} 个附件", summary.saved.len());
+    if !summary.unknown_senders.is_empty() {
+        println!(
+            "⚠️ 以下发件人不在学生名单中：\n{}",
+            summary.unknown_senders.join("\n")
+        );
+    }
+    info!("邮件接收流程完成");
+
+    let check_dir = receiver.homework_dir(homework);
+    Ok(cmd_args.check_dir.clone().or(Some(check_dir)))
+}
+
 /// 发送邮件
-fn send_email(cmd_args: &Cli, missing: &Vec<Stu>, app_config: &AppConfig) -> Result<()> {
+///
+/// 未提交的学生收到提醒邮件，提交无效的学生收到无效提交提醒，其中列出每个文件无效的原因
+fn send_email(
+    cmd_args: &Cli,
+    missing: &[Stu],
+    invalid: &[InvalidSubmission],
+    app_config: &AppConfig,
+    homework: Option<&Homework>,
+    deadline: Option<Deadline>,
+) -> Result<()> {
     if cmd_args.send {
-        let homework_name = cmd_args.homework_name.as_ref().expect("必须指定作业名称");
-        let sender = EmailSender::new(&app_config.smtp_config.username, &app_config.smtp_config)?;
+        let homework_name = homework.expect("必须指定作业").display_name();
+        let template_config = &app_config.template_config;
 
-        for student in missing {
-            match sender.send_notification_to_student(homework_name, student) {
-                Ok(_) => println!("✅ 邮件成功发送至: {}", student.email),
-                Err(e) => warn!("发送邮件到 {} 失败: {}", student.email, e),
-            }
+        let submit_to = [
+            &template_config.submit_to,
+            &app_config.imap_config.username,
+            &app_config.smtp_config.username,
+        ]
+        .into_iter()
+        .find(|address| !address.is_empty())
+        .map_or("", |address| address.as_str());
+        let deadline_text = deadline.map_or_else(|| "未指定".to_string(), |d| d.to_string());
+
+        if !missing.is_empty() {
+            let template = ReminderTemplate::load(template_config, &cmd_args.config_dir)?;
+            let status = match deadline.and_then(|deadline| deadline.overdue(Local::now())) {
+                Some(overdue) => format!("逾期 {}未提交", format_duration(overdue)),
+                None => "未提交".to_string(),
+            };
+            let reminder = Reminder {
+                template: &template,
+                homework: homework_name,
+                deadline: &deadline_text,
+                status: &status,
+                reasons: &HashMap::new(),
+                submit_to,
+            };
+            deliver_reminders(cmd_args, app_config, &reminder, missing)?;
+        }
+
+        if !invalid.is_empty() {
+            let template = ReminderTemplate::load_invalid(template_config, &cmd_args.config_dir)?;
+            let reasons: HashMap<String, String> = invalid
+                .iter()
+                .map(|invalid| (invalid.student.email.clone(), invalid.reason()))
+                .collect();
+            let students: Vec<Stu> = invalid
+                .iter()
+                .map(|invalid| invalid.student.clone())
+                .collect();
+            let reminder = Reminder {
+                template: &template,
+                homework: homework_name,
+                deadline: &deadline_text,
+                status: "提交无效",
+                reasons: &reasons,
+                submit_to,
+            };
+            deliver_reminders(cmd_args, app_config, &reminder, &students)?;
         }
 
         info!("邮件通知流程完成");
     }
     Ok(())
 }
+
+/// 向学生发送同一类提醒邮件
+///
+/// `max_concurrency` 大于1时使用异步发送器并发发送，否则使用阻塞发送器逐封发送。
+/// 暂时性错误由发送器自动重试，最终仍失败的学生会在结束时统一列出
+fn deliver_reminders(
+    cmd_args: &Cli,
+    app_config: &AppConfig,
+    reminder: &Reminder,
+    students: &[Stu],
+) -> Result<()> {
+    let smtp_config = &app_config.smtp_config;
+    if cmd_args.dry_run {
+        return preview_email(reminder, students, smtp_config, cmd_args.eml_dir.as_deref());
+    }
+
+    let results = if smtp_config.max_concurrency > 1 {
+        let runtime = tokio::runtime::Runtime::new().context("无法创建异步运行时")?;
+        runtime.block_on(async {
+            let sender = AsyncEmailSender::new(&smtp_config.username, smtp_config)?;
+            anyhow::Ok(sender.send_notifications(reminder, students).await)
+        })?
+    } else {
+        let sender = EmailSender::new(&smtp_config.username, smtp_config)?;
+        students
+            .iter()
+            .map(|student| {
+                let result = sender.send_notification_to_student(reminder, student);
+                (student, result)
+            })
+            .collect()
+    };
+
+    let mut failed = Vec::new();
+    for (student, result) in results {
+        match result {
+            Ok(_) => println!("✅ 邮件成功发送至: {}", student.email),
+            Err(e) => {
+                warn!("发送邮件到 {} 失败: {:#}", student.email, e);
+                failed.push(format!("{} <{}>", student.name, student.email));
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        println!("❌ 以下学生的提醒邮件发送失败：\n{}", failed.join("\n"));
+    }
+
+    Ok(())
+}
+
+/// 演练发送邮件，只打印邮件摘要并可选地保存为 `.eml` 文件，不连接SMTP服务器
+fn preview_email(
+    reminder: &Reminder,
+    missing: &[Stu],
+    smtp_config: &SmtpConfig,
+    eml_dir: Option<&Path>,
+) -> Result<()> {
+    let sender = DryRunSender::new(&smtp_config.username, eml_dir)?;
+
+    for (student, result) in sender.send_notifications(reminder, missing) {
+        match result {
+            Ok(preview) => {
+                println!("📝 [演练] 收件人: {} <{}>", student.name, preview.to);
+                println!("   主题: {}", preview.subject);
+                for line in preview.text.lines() {
+                    println!("   | {}", line);
+                }
+                if let Some(path) = preview.eml_path {
+                    println!("   已保存: {}", path.display());
+                }
+            }
+            Err(e) => warn!("生成发送给 {} 的邮件失败: {:#}", student.email, e),
+        }
+    }
+
+    info!("演练完成，未发送任何邮件");
+    Ok(())
+}
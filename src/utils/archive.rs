@@ -0,0 +1,663 @@
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use flate2::read::GzDecoder;
+use globset::Glob;
+use sevenz_rust::{Password, SevenZReader};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+use super::content::format_size;
+
+/// 压缩包中最多的文件数
+pub const MAX_ENTRIES: usize = 10_000;
+
+/// 解压后的最大总大小
+pub const MAX_UNPACKED_SIZE: u64 = 1 << 30;
+
+/// 最大压缩比，解压后超过 1MB 且超过该比例时视为压缩炸弹
+pub const MAX_RATIO: u64 = 100;
+
+/// RAR 文件头
+const RAR_SIGNATURE: &[u8] = b"Rar!\x1a\x07";
+
+/// 压缩包格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    SevenZip,
+
+    /// 只检查文件头，无法查看其中的文件
+    Rar,
+}
+
+/// 压缩包或文件夹中的一个文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// 以 `/` 分隔的相对路径
+    pub path: String,
+
+    /// 解压后的大小
+    pub size: u64,
+}
+
+/// 提交中必须包含的文件
+///
+/// 规则为文件名、相对路径或通配符，如 `report.pdf`、`src/main.c`、`*.c`；以 `/` 结尾时要求存在该目录，如 `src/`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequiredFiles(Vec<(String, Glob)>);
+
+/// 解压限额，防止压缩炸弹
+#[derive(Debug, Default)]
+struct Budget {
+    /// 已读取的文件数
+    entries: usize,
+
+    /// 已读取的文件解压后的总大小
+    unpacked: u64,
+}
+
+impl ArchiveKind {
+    /// 根据扩展名判断压缩包格式，不是压缩包时为None
+    ///
+    /// `.docx` 等以 zip 格式存储的文档不视为压缩包
+    pub fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        [
+            (".zip", Self::Zip),
+            (".tar", Self::Tar),
+            (".tar.gz", Self::TarGz),
+            (".tgz", Self::TarGz),
+            (".7z", Self::SevenZip),
+            (".rar", Self::Rar),
+        ]
+        .into_iter()
+        .find(|(extension, _)| name.ends_with(extension))
+        .map(|(_, kind)| kind)
+    }
+
+    /// 是否支持查看和解压该格式，RAR 没有可用的纯 Rust 实现，只检查文件头
+    pub fn is_supported(self) -> bool {
+        self != Self::Rar
+    }
+}
+
+impl fmt::Display for ArchiveKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Zip => "zip",
+            Self::Tar => "tar",
+            Self::TarGz => "tar.gz",
+            Self::SevenZip => "7z",
+            Self::Rar => "rar",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl RequiredFiles {
+    /// 编译必需文件规则
+    ///
+    /// # param
+    /// * `patterns` - 必需文件规则
+    ///
+    /// # return
+    /// * `Result<Self>` - 规则或通配符错误
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        patterns
+            .iter()
+            .map(|pattern| {
+                let glob = Glob::new(pattern.trim_end_matches('/'))
+                    .with_context(|| format!("无效的通配符: {}", pattern))?;
+                Ok((pattern.clone(), glob))
+            })
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+
+    /// 是否没有必需文件
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 找出提交中缺少的文件
+    ///
+    /// 规则同时与名称和相对路径匹配；所有文件都在同一个顶层目录中时（如 `2021001_张三/...`），
+    /// 相对路径从该目录算起
+    ///
+    /// # param
+    /// * `entries` - 提交中的所有文件
+    ///
+    /// # return
+    /// * `Vec<String>` - 缺少的文件规则
+    pub fn missing(&self, entries: &[ArchiveEntry]) -> Vec<String> {
+        let root = common_root(entries);
+        let files: Vec<&str> = entries
+            .iter()
+            .map(|entry| strip_root(&entry.path, root))
+            .collect();
+        let dirs: BTreeSet<&str> = files
+            .iter()
+            .flat_map(|file| file.match_indices('/').map(|(index, _)| &file[..index]))
+            .collect();
+
+        self.0
+            .iter()
+            .filter(|(pattern, glob)| {
+                let matcher = glob.compile_matcher();
+                let matches = |path: &&str| {
+                    let name = path.rsplit('/').next().unwrap_or(path);
+                    matcher.is_match(name) || matcher.is_match(path)
+                };
+                match pattern.ends_with('/') {
+                    true => !dirs.iter().any(matches),
+                    false => !files.iter().any(matches),
+                }
+            })
+            .map(|(pattern, _)| pattern.clone())
+            .collect()
+    }
+}
+
+impl Budget {
+    /// 记录读取的文件，超出限额时报错
+    fn take(&mut self, entry: &ArchiveEntry) -> Result<()> {
+        self.entries += 1;
+        self.unpacked += entry.size;
+        ensure!(
+            self.entries <= MAX_ENTRIES,
+            "压缩包中的文件过多，最多 {} 个",
+            MAX_ENTRIES
+        );
+        ensure!(
+            self.unpacked <= MAX_UNPACKED_SIZE,
+            "压缩包解压后超过 {}，可能是压缩炸弹",
+            format_size(MAX_UNPACKED_SIZE)
+        );
+        Ok(())
+    }
+}
+
+/// 列出压缩包中的文件，不解压到磁盘
+///
+/// 同时检查压缩包是否损坏、是否包含 `../` 或绝对路径等不安全的路径，以及文件数、解压后大小和压缩比是否超限
+///
+/// # param
+/// * `path` - 压缩包路径
+/// * `kind` - 压缩包格式
+///
+/// # return
+/// * `Result<Option<Vec<ArchiveEntry>>>` - 压缩包中的文件（不含目录），不支持查看的格式（RAR）只检查文件头，返回None
+pub fn list(path: &Path, kind: ArchiveKind) -> Result<Option<Vec<ArchiveEntry>>> {
+    let mut budget = Budget::default();
+    let mut entries = Vec::new();
+    let mut push = |entry: ArchiveEntry| -> Result<()> {
+        budget.take(&entry)?;
+        entries.push(entry);
+        Ok(())
+    };
+
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = open_zip(path)?;
+            for index in 0..archive.len() {
+                let file = archive.by_index_raw(index).context("压缩包已损坏")?;
+                if !file.is_dir() {
+                    push(ArchiveEntry {
+                        path: safe_path(file.name())?,
+                        size: file.size(),
+                    })?;
+                }
+            }
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => read_tar(path, kind, |entry, _| push(entry))?,
+        ArchiveKind::SevenZip => {
+            let reader = open_7z(path)?;
+            for file in &reader.archive().files {
+                if !file.is_directory() {
+                    push(ArchiveEntry {
+                        path: safe_path(file.name())?,
+                        size: file.size(),
+                    })?;
+                }
+            }
+        }
+        ArchiveKind::Rar => {
+            let mut signature = [0; RAR_SIGNATURE.len()];
+            let valid = File::open(path)
+                .and_then(|mut file| file.read_exact(&mut signature))
+                .is_ok_and(|_| signature == RAR_SIGNATURE);
+            ensure!(valid, "不是有效的 rar 文件，可能已损坏");
+            return Ok(None);
+        }
+    }
+
+    let unpacked: u64 = entries.iter().map(|entry| entry.size).sum();
+    let packed = fs::metadata(path)?.len().max(1);
+    ensure!(
+        unpacked <= 1 << 20 || unpacked / packed <= MAX_RATIO,
+        "压缩比过高（{} 倍），可能是压缩炸弹",
+        unpacked / packed
+    );
+
+    Ok(Some(entries))
+}
+
+/// 列出文件夹中的所有文件
+///
+/// # param
+/// * `dir` - 文件夹路径
+///
+/// # return
+/// * `Result<Vec<ArchiveEntry>>` - 按路径排序的文件
+pub fn list_dir(dir: &Path) -> Result<Vec<ArchiveEntry>> {
+    fn walk(root: &Path, relative: &str, entries: &mut Vec<ArchiveEntry>) -> Result<()> {
+        let dir = root.join(relative);
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("无法读取目录: {}", dir.display()))?
+        {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let path = match relative.is_empty() {
+                true => name,
+                false => format!("{}/{}", relative, name),
+            };
+            let metadata = entry.metadata()?;
+            match metadata.is_dir() {
+                true => walk(root, &path, entries)?,
+                false => entries.push(ArchiveEntry {
+                    path,
+                    size: metadata.len(),
+                }),
+            }
+        }
+        Ok(())
+    }
+
+    let mut entries = Vec::new();
+    walk(dir, "", &mut entries)?;
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(entries)
+}
+
+/// 将压缩包解压到目录
+///
+/// 所有文件都在同一个顶层目录中时去掉该目录，使解压结果的结构一致。解压前先检查路径与大小，
+/// 解压时按实际写入的大小再次检查，防止压缩包中记录的大小与实际不符
+///
+/// # param
+/// * `path` - 压缩包路径
+/// * `kind` - 压缩包格式
+/// * `dest` - 目标目录
+///
+/// # return
+/// * `Result<usize>` - 解压的文件数
+pub fn extract(path: &Path, kind: ArchiveKind, dest: &Path) -> Result<usize> {
+    let entries = list(path, kind)?.ok_or_else(|| anyhow!("不支持解压 {} 压缩包", kind))?;
+    let root = common_root(&entries).map(str::to_string);
+
+    let mut written = 0;
+    let mut write = |entry: &ArchiveEntry, reader: &mut dyn Read| -> Result<()> {
+        let target = dest.join(strip_root(&entry.path, root.as_deref()));
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("无法创建目录: {}", parent.display()))?;
+        }
+        let mut file =
+            File::create(&target).with_context(|| format!("无法写入: {}", target.display()))?;
+        written += io::copy(&mut reader.take(MAX_UNPACKED_SIZE - written + 1), &mut file)?;
+        ensure!(
+            written <= MAX_UNPACKED_SIZE,
+            "压缩包解压后超过 {}，可能是压缩炸弹",
+            format_size(MAX_UNPACKED_SIZE)
+        );
+        Ok(())
+    };
+
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = open_zip(path)?;
+            for index in 0..archive.len() {
+                let mut file = archive.by_index(index).context("压缩包已损坏")?;
+                if !file.is_dir() {
+                    let entry = ArchiveEntry {
+                        path: safe_path(file.name())?,
+                        size: file.size(),
+                    };
+                    write(&entry, &mut file)?;
+                }
+            }
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            read_tar(path, kind, |entry, reader| write(&entry, reader))?
+        }
+        ArchiveKind::SevenZip => {
+            let mut error = None;
+            open_7z(path)?
+                .for_each_entries(|file, reader| {
+                    if file.is_directory() {
+                        return Ok(true);
+                    }
+                    let result = safe_path(file.name()).and_then(|path| {
+                        let entry = ArchiveEntry {
+                            path,
+                            size: file.size(),
+                        };
+                        write(&entry, reader)
+                    });
+                    match result {
+                        Ok(()) => Ok(true),
+                        Err(e) => {
+                            error = Some(e);
+                            Ok(false)
+                        }
+                    }
+                })
+                .map_err(|e| anyhow!("压缩包已损坏: {}", e))?;
+            if let Some(e) = error {
+                return Err(e);
+            }
+        }
+        ArchiveKind::Rar => bail!("不支持解压 {} 压缩包，请改用 zip、7z 或 tar.gz 格式", kind),
+    }
+
+    Ok(entries.len())
+}
+
+/// 将一名学生的提交整理到一个目录中
+///
+/// 压缩包解压到目标目录，其他文件和文件夹直接复制；无法解压的压缩包原样复制
+///
+/// # param
+/// * `root` - 检查目录
+/// * `files` - 提交的文件或文件夹，相对于检查目录
+/// * `dest` - 目标目录，不存在时自动创建
+///
+/// # return
+/// * `Result<usize>` - 整理后的文件数
+pub fn collect_submission(root: &Path, files: &[PathBuf], dest: &Path) -> Result<usize> {
+    fs::create_dir_all(dest).with_context(|| format!("无法创建目录: {}", dest.display()))?;
+
+    let mut count = 0;
+    for file in files {
+        let source = root.join(file);
+        let name = file.file_name().context("无效的文件名")?;
+        count += match ArchiveKind::detect(file).filter(|kind| kind.is_supported()) {
+            Some(kind) => extract(&source, kind, dest)
+                .with_context(|| format!("无法解压: {}", source.display()))?,
+            None if source.is_dir() => copy_dir(&source, &dest.join(name))?,
+            None => {
+                fs::copy(&source, dest.join(name))
+                    .with_context(|| format!("无法复制: {}", source.display()))?;
+                1
+            }
+        };
+    }
+
+    Ok(count)
+}
+
+/// 递归复制文件夹
+fn copy_dir(source: &Path, dest: &Path) -> Result<usize> {
+    let entries = list_dir(source)?;
+    for entry in &entries {
+        let target = dest.join(&entry.path);
+        fs::create_dir_all(target.parent().unwrap_or(dest))?;
+        fs::copy(source.join(&entry.path), &target)
+            .with_context(|| format!("无法复制: {}", target.display()))?;
+    }
+
+    Ok(entries.len())
+}
+
+/// 打开 zip 压缩包
+fn open_zip(path: &Path) -> Result<ZipArchive<File>> {
+    let file = File::open(path).with_context(|| format!("无法读取: {}", path.display()))?;
+    ZipArchive::new(file).map_err(|_| anyhow!("不是有效的 zip 文件，可能已损坏"))
+}
+
+/// 打开 7z 压缩包，加密的压缩包无法打开
+fn open_7z(path: &Path) -> Result<SevenZReader<File>> {
+    SevenZReader::open(path, Password::empty())
+        .map_err(|e| anyhow!("不是有效的 7z 文件，可能已损坏或已加密: {}", e))
+}
+
+/// 依次读取 tar 压缩包中的文件，跳过目录和链接
+fn read_tar(
+    path: &Path,
+    kind: ArchiveKind,
+    mut visit: impl FnMut(ArchiveEntry, &mut dyn Read) -> Result<()>,
+) -> Result<()> {
+    let file = File::open(path).with_context(|| format!("无法读取: {}", path.display()))?;
+    let reader: Box<dyn Read> = match kind {
+        ArchiveKind::TarGz => Box::new(GzDecoder::new(file)),
+        _ => Box::new(file),
+    };
+
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries().context("压缩包已损坏")? {
+        let mut entry = entry.context("压缩包已损坏")?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = safe_path(&String::from_utf8_lossy(&entry.path_bytes()))?;
+        let size = entry.size();
+        visit(ArchiveEntry { path, size }, &mut entry)?;
+    }
+
+    Ok(())
+}
+
+/// 规范化压缩包中的路径，拒绝可能写到目标目录之外的路径（zip-slip）
+fn safe_path(name: &str) -> Result<String> {
+    let normalized = name.replace('\\', "/");
+    let parts: Vec<&str> = normalized
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect();
+    let unsafe_path = normalized.starts_with('/')
+        || parts.is_empty()
+        || parts.iter().any(|part| *part == ".." || part.contains(':'));
+    if unsafe_path {
+        bail!("压缩包中包含不安全的路径: {}", name);
+    }
+
+    Ok(parts.join("/"))
+}
+
+/// 所有文件共同的顶层目录
+fn common_root(entries: &[ArchiveEntry]) -> Option<&str> {
+    let (root, _) = entries.first()?.path.split_once('/')?;
+    entries
+        .iter()
+        .all(|entry| {
+            entry
+                .path
+                .split_once('/')
+                .is_some_and(|(first, _)| first == root)
+        })
+        .then_some(root)
+}
+
+/// 去掉路径开头的顶层目录
+fn strip_root<'a>(path: &'a str, root: Option<&str>) -> &'a str {
+    root.and_then(|root| path.strip_prefix(root))
+        .and_then(|rest| rest.strip_prefix('/'))
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    fn write_zip(path: &Path, files: &[(&str, &[u8])]) -> Result<()> {
+        let mut zip = zip::ZipWriter::new(File::create(path)?);
+        let options = SimpleFileOptions::default();
+        for (name, content) in files {
+            zip.start_file(*name, options)?;
+            zip.write_all(content)?;
+        }
+        zip.finish()?;
+        Ok(())
+    }
+
+    fn paths(entries: &[ArchiveEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.path.as_str()).collect()
+    }
+
+    #[test]
+    fn should_list_zip_tar_gz_and_7z() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let zip_path = tmp_dir.path().join("张三.zip");
+        write_zip(
+            &zip_path,
+            &[
+                ("张三/report.pdf", b"%PDF-"),
+                ("张三/src/main.c", b"int main;"),
+            ],
+        )?;
+        let tgz_path = tmp_dir.path().join("李四.tar.gz");
+        let mut tar = tar::Builder::new(GzEncoder::new(
+            File::create(&tgz_path)?,
+            Compression::default(),
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(3);
+        header.set_cksum();
+        tar.append_data(&mut header, "./src/lib.rs", &b"mod"[..])?;
+        tar.into_inner()?.finish()?;
+        let source = tmp_dir.path().join("王五");
+        fs::create_dir_all(source.join("docs"))?;
+        fs::write(source.join("docs/report.pdf"), "%PDF-")?;
+        let sevenz_path = tmp_dir.path().join("王五.7z");
+        sevenz_rust::compress_to_path(&source, &sevenz_path)?;
+
+        // When
+        let zip = list(&zip_path, ArchiveKind::Zip)?.unwrap();
+        let tgz = list(&tgz_path, ArchiveKind::TarGz)?.unwrap();
+        let sevenz = list(&sevenz_path, ArchiveKind::SevenZip)?.unwrap();
+
+        // Then
+        assert_eq!(paths(&zip), ["张三/report.pdf", "张三/src/main.c"]);
+        assert_eq!(zip[1].size, 9);
+        assert_eq!(
+            tgz,
+            [ArchiveEntry {
+                path: "src/lib.rs".into(),
+                size: 3
+            }]
+        );
+        assert_eq!(paths(&sevenz), ["docs/report.pdf"]);
+        assert_eq!(
+            ArchiveKind::detect(Path::new("a.TGZ")),
+            Some(ArchiveKind::TarGz)
+        );
+        assert_eq!(ArchiveKind::detect(Path::new("报告.docx")), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_unsafe_paths_and_bombs() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let slip = tmp_dir.path().join("slip.zip");
+        write_zip(&slip, &[("../../.bashrc", b"rm -rf ~")])?;
+        let bomb = tmp_dir.path().join("bomb.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&bomb)?);
+        zip.start_file(
+            "zeros.bin",
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated),
+        )?;
+        zip.write_all(&vec![0; 16 << 20])?;
+        zip.finish()?;
+        let rar = tmp_dir.path().join("a.rar");
+        fs::write(&rar, b"Rar!\x1a\x07\x01\x00")?;
+
+        // When & Then
+        let error = list(&slip, ArchiveKind::Zip).unwrap_err().to_string();
+        assert_eq!(error, "压缩包中包含不安全的路径: ../../.bashrc");
+        let error = list(&bomb, ArchiveKind::Zip).unwrap_err().to_string();
+        assert!(error.contains("可能是压缩炸弹"), "{}", error);
+        assert_eq!(list(&rar, ArchiveKind::Rar)?, None);
+        assert!(list(&slip, ArchiveKind::Rar).is_err());
+        let error = extract(&rar, ArchiveKind::Rar, tmp_dir.path())
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("不支持解压 rar 压缩包"), "{}", error);
+        assert!(safe_path("C:\\Windows\\win.ini").is_err());
+        assert_eq!(safe_path("./a\\b/./c.txt")?, "a/b/c.txt");
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_find_missing_required_files() -> Result<()> {
+        // Given
+        let required = RequiredFiles::new(&[
+            "report.pdf".into(),
+            "src/".into(),
+            "*.md".into(),
+            "tests/".into(),
+        ])?;
+        let entries: Vec<ArchiveEntry> =
+            ["2021001_张三/docs/report.pdf", "2021001_张三/src/main.c"]
+                .map(|path| ArchiveEntry {
+                    path: path.into(),
+                    size: 1,
+                })
+                .to_vec();
+
+        // When
+        let missing = required.missing(&entries);
+
+        // Then
+        assert_eq!(missing, ["*.md", "tests/"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_extract_into_normalized_folder() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let root = tmp_dir.path().join("submissions");
+        fs::create_dir_all(root.join("notes"))?;
+        write_zip(
+            &root.join("张三.zip"),
+            &[
+                ("作业3/report.pdf", b"%PDF-"),
+                ("作业3/src/main.c", b"int main;"),
+            ],
+        )?;
+        fs::write(root.join("notes/readme.txt"), "说明")?;
+        let dest = tmp_dir.path().join("out/张三");
+
+        // When
+        let count = collect_submission(
+            &root,
+            &[PathBuf::from("张三.zip"), PathBuf::from("notes")],
+            &dest,
+        )?;
+
+        // Then
+        assert_eq!(count, 3);
+        assert_eq!(
+            paths(&list_dir(&dest)?),
+            ["notes/readme.txt", "report.pdf", "src/main.c"]
+        );
+        assert_eq!(fs::read_to_string(dest.join("src/main.c"))?, "int main;");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,435 @@
+use anyhow::{anyhow, Result};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use zip::ZipArchive;
+
+use super::archive::{self, ArchiveEntry, ArchiveKind, RequiredFiles};
+use super::scanner::ScannedEntry;
+use super::FileIssue;
+use crate::config::app_config::Homework;
+
+/// Office Open XML 文档中必须存在的部件
+const OOXML_PARTS: [(&str, &str); 3] = [
+    ("docx", "word/document.xml"),
+    ("xlsx", "xl/workbook.xml"),
+    ("pptx", "ppt/presentation.xml"),
+];
+
+/// 以 zip 格式存储的文件类型
+const ZIP_BASED: [&str; 8] = ["zip", "docx", "xlsx", "pptx", "odt", "ods", "odp", "jar"];
+
+/// 提交内容检查规则
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentRules {
+    /// 允许提交的文件扩展名（小写，不含 `.`），为空时不限制
+    extensions: Vec<String>,
+
+    /// 最小文件大小（字节），空文件总是无效
+    min_size: u64,
+
+    /// 最大文件大小（字节），为None时不限制
+    max_size: Option<u64>,
+
+    /// 提交中必须包含的文件，在压缩包、文件夹和提交的文件中查找
+    required_files: RequiredFiles,
+}
+
+/// 必需文件的检查结果
+#[derive(Debug, Clone)]
+pub enum RequiredStatus {
+    /// 必需的文件齐全，或未配置必需的文件
+    Complete,
+
+    /// 缺少必需的文件，附带原因
+    Missing(String),
+
+    /// 可以查看的提交中缺少必需的文件，但还有不支持查看的压缩包，无法确定是否缺少
+    Unsupported(Vec<FileIssue>),
+}
+
+impl Default for ContentRules {
+    fn default() -> Self {
+        Self {
+            extensions: Vec::new(),
+            min_size: 1,
+            max_size: None,
+            required_files: RequiredFiles::default(),
+        }
+    }
+}
+
+impl ContentRules {
+    /// 根据作业定义创建检查规则，未指定作业时只检查空文件与文件结构
+    ///
+    /// # param
+    /// * `homework` - 作业定义
+    ///
+    /// # return
+    /// * `Result<Self>` - 检查规则或文件大小、通配符格式错误
+    pub fn for_homework(homework: Option<&Homework>) -> Result<Self> {
+        let Some(homework) = homework else {
+            return Ok(Self::default());
+        };
+
+        let size = |value: &str| match value.is_empty() {
+            true => Ok(None),
+            false => parse_size(value).map(Some),
+        };
+        Ok(Self {
+            extensions: homework
+                .extensions
+                .iter()
+                .map(|extension| extension.trim_start_matches('.').to_lowercase())
+                .collect(),
+            min_size: size(&homework.min_size)?.unwrap_or(0).max(1),
+            max_size: size(&homework.max_size)?,
+            required_files: RequiredFiles::new(&homework.required_files)?,
+        })
+    }
+
+    /// 检查提交的文件，目录不检查
+    ///
+    /// 压缩包会检查其中的路径和解压后的大小，但不检查必需文件，必需文件由 `check_required` 对整份提交统一检查
+    ///
+    /// # param
+    /// * `path` - 提交的文件或目录
+    ///
+    /// # return
+    /// * `Result<(), String>` - 文件无效时返回原因
+    pub fn check(&self, path: &Path) -> Result<(), String> {
+        let metadata = fs::metadata(path).map_err(|e| format!("无法读取文件: {}", e))?;
+        if metadata.is_dir() {
+            return Ok(());
+        }
+
+        // 按文件名后缀匹配，使 `tar.gz` 这样的多段扩展名也能生效
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(str::to_lowercase)
+            .unwrap_or_default();
+        let allowed = self
+            .extensions
+            .iter()
+            .any(|extension| name.ends_with(&format!(".{}", extension)));
+        if !self.extensions.is_empty() && !allowed {
+            return Err(format!(
+                "文件类型不符合要求，应为 {}",
+                self.extensions.join("/")
+            ));
+        }
+
+        let size = metadata.len();
+        if size == 0 {
+            return Err("文件为空".into());
+        }
+        if size < self.min_size {
+            return Err(format!(
+                "文件过小（{}，至少 {}）",
+                format_size(size),
+                format_size(self.min_size)
+            ));
+        }
+        if let Some(max_size) = self.max_size.filter(|max_size| size > *max_size) {
+            return Err(format!(
+                "文件过大（{}，最多 {}）",
+                format_size(size),
+                format_size(max_size)
+            ));
+        }
+
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase)
+            .unwrap_or_default();
+        check_structure(path, &extension)?;
+        match ArchiveKind::detect(path) {
+            Some(kind) => archive::list(path, kind)
+                .map(|_| ())
+                .map_err(|e| format!("{:#}", e)),
+            None => Ok(()),
+        }
+    }
+
+    /// 检查一名学生的提交中是否缺少必需的文件
+    ///
+    /// 压缩包中的文件、文件夹中的文件和提交的文件本身合在一起查找。
+    /// 缺少的文件可能在不支持查看的压缩包中时，无法确定是否缺少文件
+    ///
+    /// # param
+    /// * `root` - 检查目录
+    /// * `entries` - 该学生的有效提交
+    ///
+    /// # return
+    /// * `RequiredStatus` - 检查结果
+    pub fn check_required(&self, root: &Path, entries: &[&ScannedEntry]) -> RequiredStatus {
+        if self.required_files.is_empty() {
+            return RequiredStatus::Complete;
+        }
+
+        let mut contents = Vec::new();
+        let mut unsupported = Vec::new();
+        for entry in entries {
+            let path = root.join(&entry.path);
+            let relative = entry.path.to_string_lossy().replace('\\', "/");
+            match ArchiveKind::detect(&path) {
+                _ if entry.is_dir => {
+                    let files = archive::list_dir(&path).unwrap_or_default();
+                    contents.extend(files.into_iter().map(|file| ArchiveEntry {
+                        path: format!("{}/{}", relative, file.path),
+                        size: file.size,
+                    }));
+                }
+                Some(kind) if !kind.is_supported() => unsupported.push(FileIssue {
+                    name: relative,
+                    reason: format!("不支持查看 {} 压缩包中的文件，未检查必需的文件", kind),
+                }),
+                Some(kind) => contents.extend(
+                    archive::list(&path, kind)
+                        .ok()
+                        .flatten()
+                        .unwrap_or_default(),
+                ),
+                None => contents.push(ArchiveEntry {
+                    path: relative,
+                    size: 0,
+                }),
+            }
+        }
+
+        let missing = self.required_files.missing(&contents);
+        match (missing.is_empty(), unsupported.is_empty()) {
+            (true, _) => RequiredStatus::Complete,
+            (false, true) => {
+                RequiredStatus::Missing(format!("缺少必需的文件: {}", missing.join(", ")))
+            }
+            (false, false) => RequiredStatus::Unsupported(unsupported),
+        }
+    }
+}
+
+/// 检查常见文件类型的结构是否完整，其他类型不检查
+fn check_structure(path: &Path, extension: &str) -> Result<(), String> {
+    let mut file = File::open(path).map_err(|e| format!("无法读取文件: {}", e))?;
+
+    if extension == "pdf" {
+        let mut header = [0; 5];
+        let mut tail = Vec::new();
+        let valid_header = file.read_exact(&mut header).is_ok() && &header == b"%PDF-";
+        let size = file.seek(SeekFrom::End(0)).unwrap_or(0);
+        file.seek(SeekFrom::Start(size.saturating_sub(1024)))
+            .and_then(|_| file.read_to_end(&mut tail))
+            .map_err(|e| format!("无法读取文件: {}", e))?;
+        let has_eof = tail.windows(5).any(|window| window == b"%%EOF");
+        return match valid_header && has_eof {
+            true => Ok(()),
+            false => Err("不是有效的 PDF 文件或文件不完整".into()),
+        };
+    }
+
+    if ZIP_BASED.contains(&extension) {
+        let archive = ZipArchive::new(file)
+            .map_err(|_| format!("不是有效的 {} 文件，可能已损坏", extension))?;
+        if let Some((_, part)) = OOXML_PARTS.iter().find(|(ext, _)| *ext == extension) {
+            if archive.index_for_name(part).is_none() {
+                return Err(format!("不是有效的 {} 文件，缺少 {}", extension, part));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 解析文件大小，如 `512`、`100KB`、`2.5MB`、`1GB`（按1024进位，不区分大小写）
+///
+/// # param
+/// * `value` - 文件大小字符串
+///
+/// # return
+/// * `Result<u64>` - 字节数或格式错误
+pub fn parse_size(value: &str) -> Result<u64> {
+    let upper = value.trim().to_uppercase();
+    let number_end = upper
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(upper.len());
+    let (number, unit) = upper.split_at(number_end);
+    let multiplier: u64 = match unit.trim() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        _ => return Err(anyhow!("无效的文件大小: {}", value)),
+    };
+    let number: f64 = number
+        .parse()
+        .map_err(|_| anyhow!("无效的文件大小: {}", value))?;
+
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// 将字节数格式化为便于阅读的大小，如 `1.5 MB`
+pub fn format_size(size: u64) -> String {
+    let units = ["B", "KB", "MB", "GB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{} B", size),
+        _ => format!("{:.1} {}", value, units[unit]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    fn write_zip(path: &Path, entries: &[&str]) -> Result<()> {
+        let mut zip = zip::ZipWriter::new(File::create(path)?);
+        for entry in entries {
+            zip.start_file(*entry, SimpleFileOptions::default())?;
+            zip.write_all(b"<xml/>")?;
+        }
+        zip.finish()?;
+        Ok(())
+    }
+
+    #[test]
+    fn should_check_size_and_structure() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        let path = |name: &str| tmp_dir.path().join(name);
+        fs::write(path("empty.pdf"), "")?;
+        fs::write(path("report.pdf"), "%PDF-1.7\n...\n%%EOF\n")?;
+        fs::write(path("truncated.pdf"), "%PDF-1.7\n...")?;
+        fs::write(path("broken.zip"), "PK not really")?;
+        write_zip(
+            &path("report.docx"),
+            &["[Content_Types].xml", "word/document.xml"],
+        )?;
+        write_zip(&path("renamed.docx"), &["readme.txt"])?;
+        fs::write(path("notes.txt"), "x".repeat(2048))?;
+        let rules = ContentRules::default();
+
+        // When & Then
+        assert_eq!(rules.check(&path("empty.pdf")), Err("文件为空".into()));
+        assert_eq!(rules.check(&path("report.pdf")), Ok(()));
+        assert_eq!(
+            rules.check(&path("truncated.pdf")),
+            Err("不是有效的 PDF 文件或文件不完整".into())
+        );
+        assert_eq!(
+            rules.check(&path("broken.zip")),
+            Err("不是有效的 zip 文件，可能已损坏".into())
+        );
+        assert_eq!(rules.check(&path("report.docx")), Ok(()));
+        assert_eq!(
+            rules.check(&path("renamed.docx")),
+            Err("不是有效的 docx 文件，缺少 word/document.xml".into())
+        );
+        let limited = ContentRules {
+            max_size: Some(1024),
+            ..Default::default()
+        };
+        assert_eq!(
+            limited.check(&path("notes.txt")),
+            Err("文件过大（2.0 KB，最多 1.0 KB）".into())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_apply_homework_rules() -> Result<()> {
+        // Given
+        let homework = Homework {
+            extensions: vec![".PDF".into(), "docx".into()],
+            min_size: "1KB".into(),
+            ..Homework::named("作业3")
+        };
+
+        // When
+        let rules = ContentRules::for_homework(Some(&homework))?;
+
+        // Then
+        assert_eq!(
+            rules,
+            ContentRules {
+                extensions: vec!["pdf".into(), "docx".into()],
+                min_size: 1024,
+                max_size: None,
+                required_files: RequiredFiles::default(),
+            }
+        );
+        let tmp_dir = tempdir()?;
+        fs::write(tmp_dir.path().join("a.txt"), "x")?;
+        fs::write(tmp_dir.path().join("b.pdf"), "%PDF-%%EOF")?;
+        assert_eq!(
+            rules.check(&tmp_dir.path().join("a.txt")),
+            Err("文件类型不符合要求，应为 pdf/docx".into())
+        );
+        assert_eq!(
+            rules.check(&tmp_dir.path().join("b.pdf")),
+            Err("文件过小（10 B，至少 1.0 KB）".into())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_match_multi_part_extensions_by_suffix() -> Result<()> {
+        // Given
+        let rules = ContentRules {
+            extensions: vec!["tar.gz".into(), "tgz".into()],
+            ..Default::default()
+        };
+        let tmp_dir = tempdir()?;
+        let write_tar_gz = |name: &str| -> Result<PathBuf> {
+            let path = tmp_dir.path().join(name);
+            let mut tar =
+                tar::Builder::new(GzEncoder::new(File::create(&path)?, Compression::default()));
+            let mut header = tar::Header::new_gnu();
+            header.set_size(5);
+            header.set_cksum();
+            tar.append_data(&mut header, "report.pdf", &b"%PDF-"[..])?;
+            tar.into_inner()?.finish()?;
+            Ok(path)
+        };
+
+        // When
+        let tar_gz = write_tar_gz("张三.tar.gz")?;
+        let tgz = write_tar_gz("李四.TGZ")?;
+        let gz = write_tar_gz("王五.gz")?;
+
+        // Then
+        assert_eq!(rules.check(&tar_gz), Ok(()));
+        assert_eq!(rules.check(&tgz), Ok(()));
+        assert_eq!(
+            rules.check(&gz),
+            Err("文件类型不符合要求，应为 tar.gz/tgz".into())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_sizes() -> Result<()> {
+        assert_eq!(parse_size("512")?, 512);
+        assert_eq!(parse_size("100kb")?, 100 * 1024);
+        assert_eq!(parse_size("2.5 MB")?, 5 * 512 * 1024);
+        assert!(parse_size("10 TB").is_err());
+        assert!(parse_size("MB").is_err());
+
+        Ok(())
+    }
+}
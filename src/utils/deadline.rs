@@ -0,0 +1,173 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use std::fmt;
+
+/// 截止时间的书写格式，也用于在报告和提醒邮件中显示截止时间
+pub const DEADLINE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// 作业截止时间（本地时间）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadline(DateTime<Local>);
+
+/// 学生的作业提交状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmissionStatus {
+    /// 在截止时间前提交
+    OnTime,
+
+    /// 截止时间后提交，附带迟交的时长
+    Late(Duration),
+
+    /// 未提交
+    Missing,
+}
+
+impl Deadline {
+    /// 解析截止时间
+    ///
+    /// 支持 `2024-10-01 23:59`、`2024-10-01 23:59:59`、`2024-10-01T23:59:59+08:00`，
+    /// 只写日期时截止到当天 23:59:59
+    ///
+    /// # param
+    /// * `value` - 截止时间字符串
+    ///
+    /// # return
+    /// * `Result<Self>` - 截止时间或格式错误
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+            return Ok(Self(time.with_timezone(&Local)));
+        }
+
+        let naive = NaiveDateTime::parse_from_str(value, DEADLINE_FORMAT)
+            .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S"))
+            .or_else(|_| {
+                NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|date| {
+                    date.and_time(NaiveTime::from_hms_opt(23, 59, 59).expect("有效的时间"))
+                })
+            })
+            .map_err(|_| anyhow!("无效的截止时间: {}，应为 YYYY-MM-DD HH:MM", value))?;
+
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(Self)
+            .ok_or_else(|| anyhow!("截止时间 {} 在本地时区中不存在", value))
+    }
+
+    /// 根据提交时间判断提交状态
+    ///
+    /// # param
+    /// * `submitted_at` - 提交时间，未提交时为None
+    ///
+    /// # return
+    /// * `SubmissionStatus` - 提交状态
+    pub fn status(&self, submitted_at: Option<DateTime<Local>>) -> SubmissionStatus {
+        match submitted_at {
+            None => SubmissionStatus::Missing,
+            Some(time) if time <= self.0 => SubmissionStatus::OnTime,
+            Some(time) => SubmissionStatus::Late(time - self.0),
+        }
+    }
+
+    /// 距截止时间已过去多久，尚未截止时为None
+    pub fn overdue(&self, now: DateTime<Local>) -> Option<Duration> {
+        Some(now - self.0).filter(|overdue| *overdue > Duration::zero())
+    }
+}
+
+impl fmt::Display for Deadline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.format(DEADLINE_FORMAT))
+    }
+}
+
+impl fmt::Display for SubmissionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OnTime => write!(f, "按时提交"),
+            Self::Late(late_by) => write!(f, "迟交 {}", format_duration(*late_by)),
+            Self::Missing => write!(f, "未提交"),
+        }
+    }
+}
+
+/// 将时长格式化为最多两个单位的中文描述，如 `2天3小时`、`15分钟`
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    let parts: Vec<String> = [
+        (minutes / (24 * 60), "天"),
+        (minutes / 60 % 24, "小时"),
+        (minutes % 60, "分钟"),
+    ]
+    .into_iter()
+    .skip_while(|(value, _)| *value == 0)
+    .take(2)
+    .filter(|(value, _)| *value > 0)
+    .map(|(value, unit)| format!("{}{}", value, unit))
+    .collect();
+
+    match parts.is_empty() {
+        true => "不到1分钟".into(),
+        false => parts.concat(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(value: &str) -> DateTime<Local> {
+        let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").unwrap();
+        Local.from_local_datetime(&naive).unwrap()
+    }
+
+    #[test]
+    fn should_parse_deadline_formats() -> Result<()> {
+        assert_eq!(
+            Deadline::parse("2024-10-01 23:59")?,
+            Deadline(local("2024-10-01 23:59:00"))
+        );
+        assert_eq!(
+            Deadline::parse("2024-10-01")?,
+            Deadline(local("2024-10-01 23:59:59"))
+        );
+        assert_eq!(
+            Deadline::parse("2024-10-01T15:59:59Z")?.0,
+            DateTime::parse_from_rfc3339("2024-10-01T23:59:59+08:00")?
+        );
+        assert!(Deadline::parse("下周一").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_classify_submissions() -> Result<()> {
+        // Given
+        let deadline = Deadline::parse("2024-10-01 23:59")?;
+
+        // When & Then
+        assert_eq!(
+            deadline.status(Some(local("2024-10-01 23:59:00"))),
+            SubmissionStatus::OnTime
+        );
+        let late = deadline.status(Some(local("2024-10-03 03:14:00")));
+        assert_eq!(
+            late,
+            SubmissionStatus::Late(Duration::minutes(27 * 60 + 15))
+        );
+        assert_eq!(late.to_string(), "迟交 1天3小时");
+        assert_eq!(deadline.status(None), SubmissionStatus::Missing);
+        assert_eq!(deadline.overdue(local("2024-10-01 12:00:00")), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_format_duration() {
+        assert_eq!(format_duration(Duration::seconds(30)), "不到1分钟");
+        assert_eq!(format_duration(Duration::minutes(15)), "15分钟");
+        assert_eq!(format_duration(Duration::minutes(61)), "1小时1分钟");
+        assert_eq!(format_duration(Duration::minutes(2 * 24 * 60 + 5)), "2天");
+    }
+}
@@ -0,0 +1,190 @@
+use crate::config::app_config::{CheckConfig, Homework};
+use crate::email::subject_filter::REGEX_PREFIX;
+use anyhow::{bail, Context, Result};
+use regex::{Regex, RegexBuilder};
+
+/// 文件名模式中可以使用的占位符
+pub const PLACEHOLDERS: [&str; 4] = ["{id}", "{name}", "{class}", "{homework}"];
+
+/// 提交文件名模式
+///
+/// 用于检查文件是否符合命名规范，并从文件名中取出学号、姓名以确定文件属于哪名学生
+#[derive(Debug)]
+pub struct FilenamePattern {
+    /// 编译后的文件名匹配规则，包含 `id`、`name`、`class` 命名捕获组
+    regex: Regex,
+}
+
+/// 从文件名中取出的学生信息
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FilenameMatch {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub class: Option<String>,
+}
+
+impl FilenamePattern {
+    /// 根据检查配置获取指定作业的文件名模式
+    ///
+    /// 优先使用作业定义中的 `filename_pattern`，否则使用通用的 `check_config.filename_pattern`
+    ///
+    /// # param
+    /// * `check_config` - 作业检查配置
+    /// * `homework` - 作业定义
+    ///
+    /// # return
+    /// * `Result<Option<Self>>` - 文件名模式，未配置时为None
+    pub fn for_homework(
+        check_config: &CheckConfig,
+        homework: Option<&Homework>,
+    ) -> Result<Option<Self>> {
+        let pattern = homework
+            .map(|homework| homework.filename_pattern.as_str())
+            .filter(|pattern| !pattern.is_empty())
+            .unwrap_or(&check_config.filename_pattern);
+        if pattern.is_empty() {
+            return Ok(None);
+        }
+
+        Self::from_pattern(pattern, homework.map(Homework::display_name)).map(Some)
+    }
+
+    /// 根据模式字符串创建文件名模式
+    ///
+    /// 以 `regex:` 开头的模式按正则表达式处理，使用 `(?P<id>...)`、`(?P<name>...)` 命名捕获组取出学号与姓名。
+    /// 否则按通配符处理（忽略大小写）：`{id}` 匹配学号，`{name}` 匹配姓名，`{class}` 匹配班级，
+    /// `{homework}` 替换为作业名称，`{pdf|docx}` 匹配其中任意一项，`*` 匹配任意字符序列，`?` 匹配单个字符。
+    /// 两种模式都必须能取出学号或姓名
+    ///
+    /// # param
+    /// * `pattern` - 模式字符串，如 `{id}_{name}_作业3.{pdf|docx}`
+    /// * `homework_name` - 作业名称，未指定时 `{homework}` 匹配任意内容
+    ///
+    /// # return
+    /// * `Result<Self>` - 文件名模式或模式错误
+    pub fn from_pattern(pattern: &str, homework_name: Option<&str>) -> Result<Self> {
+        let homework = homework_name.map_or_else(|| ".+?".to_string(), regex::escape);
+        let regex = match pattern.strip_prefix(REGEX_PREFIX) {
+            Some(regex) => Regex::new(&regex.replace("{homework}", &homework)),
+            None => RegexBuilder::new(&template_to_regex(pattern, &homework)?)
+                .case_insensitive(true)
+                .build(),
+        }
+        .with_context(|| format!("无效的文件名模式: {}", pattern))?;
+
+        if !regex
+            .capture_names()
+            .flatten()
+            .any(|name| name == "id" || name == "name")
+        {
+            bail!("文件名模式中必须包含 {{id}} 或 {{name}}: {}", pattern);
+        }
+
+        Ok(Self { regex })
+    }
+
+    /// 匹配文件名，不符合模式时返回None
+    pub fn matches(&self, filename: &str) -> Option<FilenameMatch> {
+        let captures = self.regex.captures(filename)?;
+        let capture = |name: &str| captures.name(name).map(|m| m.as_str().to_string());
+
+        Some(FilenameMatch {
+            id: capture("id"),
+            name: capture("name"),
+            class: capture("class"),
+        })
+    }
+}
+
+/// 将通配符模式转换为完整匹配的正则表达式
+fn template_to_regex(template: &str, homework: &str) -> Result<String> {
+    let mut regex = String::from("^");
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                let placeholder: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                match placeholder.as_str() {
+                    "id" => regex.push_str("(?P<id>[0-9A-Za-z]+)"),
+                    "name" => regex.push_str("(?P<name>.+?)"),
+                    "class" => regex.push_str("(?P<class>.+?)"),
+                    "homework" => regex.push_str(homework),
+                    choices if choices.contains('|') => {
+                        let choices: Vec<String> = choices.split('|').map(regex::escape).collect();
+                        regex.push_str(&format!("(?:{})", choices.join("|")));
+                    }
+                    other => bail!(
+                        "未知的占位符 {{{}}}，可用的占位符: {}，或用 {{a|b}} 表示多个可选项",
+                        other,
+                        PLACEHOLDERS.join(" ")
+                    ),
+                }
+            }
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+
+    Ok(regex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id_and_name(m: Option<FilenameMatch>) -> Option<(String, String)> {
+        m.map(|m| (m.id.unwrap_or_default(), m.name.unwrap_or_default()))
+    }
+
+    #[test]
+    fn should_capture_id_and_name_from_template() -> Result<()> {
+        // Given
+        let pattern =
+            FilenamePattern::from_pattern("{id}_{name}_{homework}.{pdf|docx}", Some("作业3"))?;
+
+        // When & Then
+        assert_eq!(
+            id_and_name(pattern.matches("2021001_张三_作业3.PDF")),
+            Some(("2021001".into(), "张三".into()))
+        );
+        assert!(pattern.matches("2021001_张三_作业3.zip").is_none());
+        assert!(pattern.matches("张三_2021001_作业3.pdf").is_none());
+        assert!(pattern.matches("2021001_张三_作业4.pdf").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_capture_from_regex() -> Result<()> {
+        // Given
+        let pattern =
+            FilenamePattern::from_pattern(r"regex:^(?P<name>\p{Han}+)-{homework}\.pdf$", None)?;
+
+        // When & Then
+        assert_eq!(
+            pattern.matches("李四-hw3.pdf").and_then(|m| m.name),
+            Some("李四".into())
+        );
+        assert!(pattern.matches("lisi-hw3.pdf").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_invalid_patterns() {
+        for pattern in [
+            "{id}_{nmae}.pdf",
+            "作业3.pdf",
+            "regex:(?P<id>\\d+",
+            "regex:\\d+",
+        ] {
+            assert!(
+                FilenamePattern::from_pattern(pattern, None).is_err(),
+                "{}",
+                pattern
+            );
+        }
+    }
+}
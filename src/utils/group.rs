@@ -0,0 +1,179 @@
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::Path;
+
+use super::scanner::{self, ScanOptions};
+use super::{display_path, FileIssue};
+use crate::config::{app_config::StuConfig, stu::Stu};
+
+/// 文件名中的组号，如 `第3组`、`group03`、`G3`
+static GROUP_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)第\s*(\d+)\s*组|group[\s_-]*(\d+)|(?:^|[^a-z])g[\s_-]?(\d+)").unwrap()
+});
+
+/// 小组作业检查结果
+#[derive(Debug, Default)]
+pub struct GroupReport {
+    /// 未提交作业的小组，按组号排序
+    pub missing: Vec<MissingGroup>,
+
+    /// 文件名中没有组号或组号超出范围的文件
+    pub unmatched: Vec<FileIssue>,
+}
+
+/// 未提交作业的小组
+#[derive(Debug, Clone)]
+pub struct MissingGroup {
+    /// 组号，从1开始
+    pub number: u32,
+
+    /// 学生名单中属于该组的学生，名单未填写小组时为空
+    pub members: Vec<Stu>,
+}
+
+/// 从文件名中取出组号
+///
+/// # param
+/// * `name` - 文件名或目录名
+///
+/// # return
+/// * `Option<u32>` - 组号，文件名中没有组号时为None
+pub fn group_number(name: &str) -> Option<u32> {
+    GROUP_REGEX
+        .captures(name)?
+        .iter()
+        .skip(1)
+        .flatten()
+        .next()
+        .and_then(|number| number.as_str().parse().ok())
+}
+
+/// 检查第1组到第 `group_cnt` 组中哪些小组未提交作业
+///
+/// 递归扫描时，文件名和所在目录名中的组号都可以用来确定提交属于哪个小组
+///
+/// # param
+/// * `stu_config` - 学生配置，用于列出未提交小组的成员
+/// * `dir` - 要检查的目录路径，默认为当前目录
+/// * `group_cnt` - 小组数量
+/// * `options` - 目录扫描规则
+///
+/// # return
+/// * `Result<GroupReport>` - 检查结果或错误
+pub fn check_groups(
+    stu_config: &StuConfig,
+    dir: Option<&Path>,
+    group_cnt: u32,
+    options: &ScanOptions,
+) -> Result<GroupReport> {
+    let dir_path = dir.unwrap_or_else(|| Path::new("."));
+    let entries = scanner::scan(dir_path, options)?;
+
+    let mut report = GroupReport::default();
+    let mut submitted = vec![false; group_cnt as usize];
+    for entry in &entries {
+        let reason = match entry.names.iter().find_map(|name| group_number(name)) {
+            Some(number) if (1..=group_cnt).contains(&number) => {
+                submitted[number as usize - 1] = true;
+                continue;
+            }
+            Some(number) => format!("组号 {} 超出范围 1-{}", number, group_cnt),
+            None => "文件名中没有组号".to_string(),
+        };
+        report.unmatched.push(FileIssue {
+            name: display_path(entry),
+            reason,
+        });
+    }
+
+    report.missing = (1..=group_cnt)
+        .zip(submitted)
+        .filter(|(_, submitted)| !submitted)
+        .map(|(number, _)| MissingGroup {
+            number,
+            members: stu_config
+                .list
+                .iter()
+                .filter(|stu| stu.group == Some(number))
+                .cloned()
+                .collect(),
+        })
+        .collect();
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn should_detect_group_numbers() {
+        for (name, expected) in [
+            ("第3组_实验报告.pdf", Some(3)),
+            ("第 12 组.zip", Some(12)),
+            ("Group03-report.docx", Some(3)),
+            ("group_4.zip", Some(4)),
+            ("G5_报告.pdf", Some(5)),
+            ("实验二-g6.pdf", Some(6)),
+            ("PNG3.zip", None),
+            ("张三_作业3.pdf", None),
+        ] {
+            assert_eq!(group_number(name), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn should_report_missing_groups_with_members() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        for filename in ["第1组.zip", "group03.pdf", "G7.pdf", "说明.txt"] {
+            fs::write(tmp_dir.path().join(filename), "")?;
+        }
+        let mut stu_config = StuConfig::default();
+        stu_config.list = [("张三", Some(2)), ("李四", Some(1)), ("王五", Some(2))]
+            .map(|(name, group)| Stu {
+                name: name.into(),
+                email: format!("{}@example.com", name),
+                group,
+                ..Default::default()
+            })
+            .to_vec();
+
+        // When
+        let report = check_groups(
+            &stu_config,
+            Some(tmp_dir.path()),
+            4,
+            &ScanOptions::default(),
+        )?;
+
+        // Then
+        let missing: Vec<(u32, Vec<&str>)> = report
+            .missing
+            .iter()
+            .map(|group| {
+                let members = group.members.iter().map(|stu| stu.name.as_str()).collect();
+                (group.number, members)
+            })
+            .collect();
+        assert_eq!(missing, [(2, vec!["张三", "王五"]), (4, vec![])]);
+        let unmatched: Vec<(&str, &str)> = report
+            .unmatched
+            .iter()
+            .map(|file| (file.name.as_str(), file.reason.as_str()))
+            .collect();
+        assert_eq!(
+            unmatched,
+            [
+                ("G7.pdf", "组号 7 超出范围 1-4"),
+                ("说明.txt", "文件名中没有组号"),
+            ]
+        );
+
+        Ok(())
+    }
+}
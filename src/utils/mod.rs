@@ -1,32 +1,679 @@
-use anyhow::{Context, Result};
-use std::fs;
-use std::path::Path;
+pub mod archive;
+pub mod content;
+pub mod deadline;
+pub mod filename_pattern;
+pub mod group;
+pub mod scanner;
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use crate::config::{app_config::StuConfig, stu::Stu};
+use content::{ContentRules, RequiredStatus};
+use filename_pattern::{FilenameMatch, FilenamePattern};
+use scanner::{ScanOptions, ScannedEntry};
+
+/// 作业检查结果
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    /// 已提交作业的学生，按名单顺序排列
+    pub submitted: Vec<Submitted>,
+
+    /// 未提交作业的学生
+    pub missing: Vec<Stu>,
+
+    /// 提交的文件全部无效的学生，不计入已提交
+    pub invalid: Vec<InvalidSubmission>,
+
+    /// 不符合命名规范或无法确定属于哪名学生的文件，仅在配置了文件名模式时检查
+    pub unmatched: Vec<FileIssue>,
+
+    /// 格式不受支持、无法检查必需文件的压缩包，提交的学生计入已提交
+    pub unchecked: Vec<FileIssue>,
+}
+
+/// 一名学生的提交
+#[derive(Debug, Clone)]
+pub struct Submitted {
+    /// 提交作业的学生
+    pub student: Stu,
+
+    /// 提交时间，取所有提交中最晚的修改时间；通过邮件接收的附件为邮件的接收时间
+    pub submitted_at: Option<DateTime<Local>>,
+
+    /// 有效的提交文件或文件夹，相对于检查目录
+    pub files: Vec<PathBuf>,
+}
+
+/// 一名学生的无效提交
+#[derive(Debug, Clone)]
+pub struct InvalidSubmission {
+    /// 提交作业的学生
+    pub student: Stu,
+
+    /// 该学生提交的文件及其无效的原因
+    pub files: Vec<FileIssue>,
+}
+
+impl InvalidSubmission {
+    /// 所有文件无效原因的汇总，用于报告和提醒邮件
+    pub fn reason(&self) -> String {
+        self.files
+            .iter()
+            .map(|file| format!("{}: {}", file.name, file.reason))
+            .collect::<Vec<_>>()
+            .join("；")
+    }
+}
+
+/// 有问题的文件：无法对应到学生，或内容无效
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileIssue {
+    /// 相对于检查目录的路径
+    pub name: String,
+
+    /// 原因
+    pub reason: String,
+}
 
 /// 检查学生列表，找出未提交作业的学生
-/// 
+///
+/// 配置了文件名模式时，只有符合模式的文件才算提交，并按文件名中的学号（没有学号时按姓名）确定所属学生；
+/// 否则文件名包含学生的学号，或包含姓名且名单中没有同名学生时，视为该学生已提交。
+/// 递归扫描时，文件名和所在目录名都参与匹配。学生提交的文件都不符合内容检查规则（类型、大小、文件结构）、
+/// 提交的文件夹为空，或有效的提交中缺少必需的文件时，视为无效提交
+///
 /// # param
 /// * `stu_config` - 学生配置
 /// * `dir` - 要检查的目录路径，默认为当前目录
-pub fn check_missing(stu_config: &StuConfig, dir: Option<&Path>) -> Result<Vec<Stu>> {
+/// * `pattern` - 提交文件名模式
+/// * `rules` - 提交内容检查规则
+/// * `options` - 目录扫描规则
+///
+/// # return
+/// * `Result<CheckReport>` - 检查结果或错误
+pub fn check_submissions(
+    stu_config: &StuConfig,
+    dir: Option<&Path>,
+    pattern: Option<&FilenamePattern>,
+    rules: &ContentRules,
+    options: &ScanOptions,
+) -> Result<CheckReport> {
     let dir_path = dir.unwrap_or_else(|| Path::new("."));
-    
-    let filenames: Vec<String> = fs::read_dir(dir_path)
-        .with_context(|| format!("无法读取目录: {}", dir_path.display()))?
-        .filter_map(|entry| entry.ok().and_then(|e| e.file_name().into_string().ok()))
-        .collect();
+    let mut report = CheckReport::default();
+    let entries = scanner::scan(dir_path, options)?;
+
+    // 同名学生无法通过姓名区分，只能按学号匹配
+    let mut name_counts: HashMap<&str, usize> = HashMap::new();
+    for stu in &stu_config.list {
+        *name_counts.entry(stu.name.as_str()).or_default() += 1;
+    }
+
+    // 每名学生提交的文件
+    let mut files: Vec<Vec<&ScannedEntry>> = vec![Vec::new(); stu_config.list.len()];
+    match pattern {
+        Some(pattern) => {
+            for entry in &entries {
+                // 依次尝试文件名和各级目录名，取第一个能对应到学生的名称
+                let mut reason = None;
+                let owner = entry
+                    .names
+                    .iter()
+                    .filter_map(|name| pattern.matches(name))
+                    .find_map(|captured| {
+                        match find_owner(&stu_config.list, &name_counts, &captured) {
+                            Ok(index) => Some(index),
+                            Err(e) => {
+                                reason.get_or_insert(e);
+                                None
+                            }
+                        }
+                    });
+                match owner {
+                    Some(index) => files[index].push(entry),
+                    None => report.unmatched.push(FileIssue {
+                        name: display_path(entry),
+                        reason: reason.unwrap_or_else(|| "不符合文件名模式".to_string()),
+                    }),
+                }
+            }
+        }
+        None => {
+            for (stu, files) in stu_config.list.iter().zip(&mut files) {
+                let unique_name = name_counts[stu.name.as_str()] == 1;
+                files.extend(entries.iter().filter(|entry| {
+                    entry
+                        .names
+                        .iter()
+                        .any(|name| stu.id_in(name) || (unique_name && name.contains(&stu.name)))
+                }));
+            }
+        }
+    }
+
+    for (stu, files) in stu_config.list.iter().zip(files) {
+        if files.is_empty() {
+            report.missing.push(stu.clone());
+            continue;
+        }
+
+        let (valid, issues): (Vec<_>, Vec<_>) = files
+            .into_iter()
+            .map(|entry| (entry, check_content(dir_path, entry, rules, options)))
+            .partition(|(_, result)| result.is_ok());
+        let valid: Vec<&ScannedEntry> = valid.into_iter().map(|(entry, _)| entry).collect();
+        let mut problems: Vec<FileIssue> = issues
+            .into_iter()
+            .filter_map(|(entry, result)| {
+                result.err().map(|reason| FileIssue {
+                    name: display_path(entry),
+                    reason,
+                })
+            })
+            .collect();
+
+        // 只要有一份提交有效，且所有有效提交中包含必需的文件（或无法查看），即视为已提交
+        if !valid.is_empty() {
+            let status = rules.check_required(dir_path, &valid);
+            if let RequiredStatus::Unsupported(files) = &status {
+                report.unchecked.extend(files.iter().cloned());
+            }
+            match status {
+                RequiredStatus::Complete | RequiredStatus::Unsupported(_) => {
+                    report.submitted.push(Submitted {
+                        student: stu.clone(),
+                        submitted_at: valid
+                            .iter()
+                            .filter_map(|entry| entry.modified)
+                            .max()
+                            .map(DateTime::from),
+                        files: valid.iter().map(|entry| entry.path.clone()).collect(),
+                    });
+                    continue;
+                }
+                RequiredStatus::Missing(reason) => problems.push(FileIssue {
+                    name: valid
+                        .iter()
+                        .map(|entry| display_path(entry))
+                        .collect::<Vec<_>>()
+                        .join("、"),
+                    reason,
+                }),
+            }
+        }
+        report.invalid.push(InvalidSubmission {
+            student: stu.clone(),
+            files: problems,
+        });
+    }
+
+    Ok(report)
+}
 
-    let missing: Vec<Stu> = stu_config
-        .list
+/// 检查一份提交的内容
+///
+/// 文件夹中没有（未被忽略的）文件时无效，否则其中的每个文件都按同样的规则检查，
+/// 原因前注明文件在文件夹中的路径
+fn check_content(
+    root: &Path,
+    entry: &ScannedEntry,
+    rules: &ContentRules,
+    options: &ScanOptions,
+) -> Result<(), String> {
+    if !entry.is_dir {
+        return rules.check(&root.join(&entry.path));
+    }
+    if entry.modified.is_none() {
+        return Err("文件夹为空".into());
+    }
+
+    let problems: Vec<String> = scanner::files_in(root, &entry.path, options)
         .iter()
-        .filter(|stu| {
-            !filenames
+        .filter_map(|path| {
+            let reason = rules.check(&root.join(path)).err()?;
+            let inner = path.strip_prefix(&entry.path).unwrap_or(path);
+            let inner = inner
                 .iter()
-                .any(|filename| filename.contains(&stu.name))
+                .map(|part| part.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            Some(format!("{}: {}", inner, reason))
         })
-        .cloned()
         .collect();
+    match problems.is_empty() {
+        true => Ok(()),
+        false => Err(problems.join("；")),
+    }
+}
+
+/// 以 `/` 分隔的相对路径，用于输出
+fn display_path(entry: &ScannedEntry) -> String {
+    entry
+        .names
+        .iter()
+        .rev()
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 根据文件名中取出的学号或姓名确定文件属于哪名学生
+///
+/// # return
+/// * `Result<usize, String>` - 学生在名单中的位置，或无法确定的原因
+fn find_owner(
+    students: &[Stu],
+    name_counts: &HashMap<&str, usize>,
+    captured: &FilenameMatch,
+) -> Result<usize, String> {
+    if let Some(id) = &captured.id {
+        let index = students
+            .iter()
+            .position(|stu| stu.id.as_deref() == Some(id.as_str()))
+            .ok_or_else(|| format!("学号 {} 不在学生名单中", id))?;
+        return match &captured.name {
+            Some(name) if *name != students[index].name => Err(format!(
+                "姓名 {} 与学号 {} 对应的学生 {} 不一致",
+                name, id, students[index].name
+            )),
+            _ => Ok(index),
+        };
+    }
+
+    let name = captured.name.as_deref().unwrap_or_default();
+    match name_counts.get(name) {
+        None => Err(format!("姓名 {} 不在学生名单中", name)),
+        Some(1) => Ok(students.iter().position(|stu| stu.name == name).unwrap()),
+        Some(_) => Err(format!("名单中有多名学生叫 {}，文件名中需要包含学号", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::app_config::CheckConfig;
+    use std::fs;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn student(id: Option<&str>, name: &str) -> Stu {
+        Stu {
+            id: id.map(str::to_string),
+            name: name.into(),
+            email: format!("{}@example.com", name),
+            ..Default::default()
+        }
+    }
+
+    fn stu_config() -> StuConfig {
+        let mut stu_config = StuConfig::default();
+        stu_config.list = vec![
+            student(Some("2021001"), "张三"),
+            student(Some("2021002"), "张三"),
+            student(Some("2021003"), "李四"),
+            student(None, "王五"),
+        ];
+        stu_config
+    }
+
+    /// 按扩展名生成结构完整的文件内容
+    fn content(filename: &str) -> Result<Vec<u8>> {
+        if filename.ends_with(".pdf") {
+            return Ok(b"%PDF-1.7\n%%EOF\n".to_vec());
+        }
+        if filename.ends_with(".docx") {
+            return zip_bytes(&["[Content_Types].xml", "word/document.xml"]);
+        }
+        Ok("作业".as_bytes().to_vec())
+    }
+
+    fn zip_bytes(files: &[&str]) -> Result<Vec<u8>> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for file in files {
+            zip.start_file(*file, zip::write::SimpleFileOptions::default())?;
+            zip.write_all(b"<xml/>")?;
+        }
+        Ok(zip.finish()?.into_inner())
+    }
+
+    fn submit(filenames: &[&str]) -> Result<tempfile::TempDir> {
+        let tmp_dir = tempdir()?;
+        for filename in filenames {
+            let path = tmp_dir.path().join(filename);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, content(filename)?)?;
+        }
+        Ok(tmp_dir)
+    }
+
+    #[test]
+    fn should_match_duplicate_names_by_id() -> Result<()> {
+        // Given
+        let tmp_dir = submit(&["2021002_张三.pdf", "李四_作业1.docx"])?;
+
+        // When
+        let report = check_submissions(
+            &stu_config(),
+            Some(tmp_dir.path()),
+            None,
+            &ContentRules::default(),
+            &ScanOptions::default(),
+        )?;
+
+        // Then
+        let missing: Vec<String> = report.missing.iter().map(Stu::label).collect();
+        assert_eq!(missing, ["2021001_张三", "王五"]);
+        assert!(report.unmatched.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_check_naming_convention_with_pattern() -> Result<()> {
+        // Given
+        let tmp_dir = submit(&[
+            "2021001_张三_作业3.pdf",
+            "2021002_李四_作业3.pdf",
+            "2021003_李四_作业3.txt",
+            "2021009_赵六_作业3.pdf",
+            "王五_作业3.docx",
+        ])?;
+        let pattern =
+            FilenamePattern::from_pattern("{id}_{name}_{homework}.{pdf|docx}", Some("作业3"))?;
+        let name_pattern = FilenamePattern::from_pattern("{name}_{homework}.*", Some("作业3"))?;
+
+        // When
+        let report = check_submissions(
+            &stu_config(),
+            Some(tmp_dir.path()),
+            Some(&pattern),
+            &ContentRules::default(),
+            &ScanOptions::default(),
+        )?;
+        let by_name = check_submissions(
+            &stu_config(),
+            Some(tmp_dir.path()),
+            Some(&name_pattern),
+            &ContentRules::default(),
+            &ScanOptions::default(),
+        )?;
+
+        // Then
+        let missing: Vec<String> = report.missing.iter().map(Stu::label).collect();
+        assert_eq!(missing, ["2021002_张三", "2021003_李四", "王五"]);
+        let unmatched: Vec<(&str, &str)> = report
+            .unmatched
+            .iter()
+            .map(|file| (file.name.as_str(), file.reason.as_str()))
+            .collect();
+        assert_eq!(
+            unmatched,
+            [
+                (
+                    "2021002_李四_作业3.pdf",
+                    "姓名 李四 与学号 2021002 对应的学生 张三 不一致"
+                ),
+                ("2021003_李四_作业3.txt", "不符合文件名模式"),
+                ("2021009_赵六_作业3.pdf", "学号 2021009 不在学生名单中"),
+                ("王五_作业3.docx", "不符合文件名模式"),
+            ]
+        );
+        let missing: Vec<String> = by_name.missing.iter().map(Stu::label).collect();
+        assert_eq!(missing, ["2021001_张三", "2021002_张三", "2021003_李四"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_match_folder_names_when_recursive() -> Result<()> {
+        // Given
+        let tmp_dir = submit(&[
+            "2021001_张三/src/main.c",
+            "2021003_李四/~$报告.docx",
+            "作业3/王五/报告.pdf",
+            "作业3/赵六/报告.pdf",
+        ])?;
+        let options = ScanOptions::from_config(&CheckConfig {
+            recursive: true,
+            ..Default::default()
+        })?;
+        let pattern = FilenamePattern::from_pattern("{name}", None)?;
+
+        // When
+        let rules = ContentRules::default();
+        let report =
+            check_submissions(&stu_config(), Some(tmp_dir.path()), None, &rules, &options)?;
+        let by_pattern = check_submissions(
+            &stu_config(),
+            Some(tmp_dir.path()),
+            Some(&pattern),
+            &rules,
+            &options,
+        )?;
+
+        // Then
+        let missing: Vec<String> = report.missing.iter().map(Stu::label).collect();
+        assert_eq!(missing, ["2021002_张三", "2021003_李四"]);
+        let unmatched: Vec<(&str, &str)> = by_pattern
+            .unmatched
+            .iter()
+            .map(|file| (file.name.as_str(), file.reason.as_str()))
+            .collect();
+        assert_eq!(
+            unmatched,
+            [
+                ("2021001_张三/src/main.c", "姓名 main.c 不在学生名单中"),
+                ("作业3/赵六/报告.pdf", "姓名 报告.pdf 不在学生名单中"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_invalid_submissions_with_reasons() -> Result<()> {
+        // Given
+        let tmp_dir = submit(&["2021003_李四.pdf", "2021003_李四.txt"])?;
+        fs::write(tmp_dir.path().join("2021001_张三.pdf"), "")?;
+        fs::write(tmp_dir.path().join("2021002_张三.docx"), "not a zip")?;
+        fs::create_dir(tmp_dir.path().join("王五"))?;
+        let rules = ContentRules::for_homework(Some(&crate::config::app_config::Homework {
+            extensions: vec!["pdf".into(), "docx".into()],
+            ..Default::default()
+        }))?;
+
+        // When
+        let report = check_submissions(
+            &stu_config(),
+            Some(tmp_dir.path()),
+            None,
+            &rules,
+            &ScanOptions::default(),
+        )?;
+
+        // Then
+        let submitted: Vec<String> = report.submitted.iter().map(|s| s.student.label()).collect();
+        assert_eq!(submitted, ["2021003_李四"]);
+        assert!(report.missing.is_empty());
+        let invalid: Vec<(String, String)> = report
+            .invalid
+            .iter()
+            .map(|invalid| (invalid.student.label(), invalid.reason()))
+            .collect();
+        assert_eq!(
+            invalid,
+            [
+                ("2021001_张三".into(), "2021001_张三.pdf: 文件为空".into()),
+                (
+                    "2021002_张三".into(),
+                    "2021002_张三.docx: 不是有效的 docx 文件，可能已损坏".into()
+                ),
+                ("王五".into(), "王五: 文件夹为空".into()),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_check_files_nested_in_submission_folders() -> Result<()> {
+        // Given
+        let tmp_dir = submit(&[
+            "2021001_张三/报告.pdf",
+            "2021003_李四/报告.pdf",
+            "2021003_李四/附录/数据.docx",
+        ])?;
+        let path = |name: &str| tmp_dir.path().join(name);
+        fs::create_dir(path("2021001_张三/附录"))?;
+        fs::write(path("2021001_张三/附录/数据.docx"), "not a zip")?;
+        fs::write(path("2021001_张三/附录/笔记.txt"), "note")?;
+        fs::write(path("2021003_李四/.DS_Store"), "finder")?;
+        let rules = ContentRules::for_homework(Some(&crate::config::app_config::Homework {
+            extensions: vec!["pdf".into(), "docx".into()],
+            ..Default::default()
+        }))?;
+
+        // When
+        let report = check_submissions(
+            &stu_config(),
+            Some(tmp_dir.path()),
+            None,
+            &rules,
+            &ScanOptions::default(),
+        )?;
+
+        // Then
+        let submitted: Vec<String> = report.submitted.iter().map(|s| s.student.label()).collect();
+        assert_eq!(submitted, ["2021003_李四"]);
+        let invalid: Vec<(String, String)> = report
+            .invalid
+            .iter()
+            .map(|invalid| (invalid.student.label(), invalid.reason()))
+            .collect();
+        assert_eq!(
+            invalid,
+            [(
+                "2021001_张三".into(),
+                "2021001_张三: 附录/数据.docx: 不是有效的 docx 文件，可能已损坏；\
+                 附录/笔记.txt: 文件类型不符合要求，应为 pdf/docx"
+                    .into()
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_check_required_files_in_archives_and_folders() -> Result<()> {
+        // Given
+        let tmp_dir = submit(&["2021003_李四/report.pdf", "2021003_李四/src/main.c"])?;
+        let path = |name: &str| tmp_dir.path().join(name);
+        fs::write(
+            path("2021001_张三.zip"),
+            zip_bytes(&["作业3/report.pdf", "作业3/src/main.c"])?,
+        )?;
+        fs::write(path("2021002_张三.zip"), zip_bytes(&["report.pdf"])?)?;
+        fs::write(path("王五.rar"), b"Rar!\x1a\x07\x01\x00")?;
+        let rules = ContentRules::for_homework(Some(&crate::config::app_config::Homework {
+            required_files: vec!["report.pdf".into(), "src/".into()],
+            ..Default::default()
+        }))?;
+
+        // When
+        let report = check_submissions(
+            &stu_config(),
+            Some(tmp_dir.path()),
+            None,
+            &rules,
+            &ScanOptions::default(),
+        )?;
+
+        // Then
+        let submitted: Vec<(String, Vec<PathBuf>)> = report
+            .submitted
+            .iter()
+            .map(|submitted| (submitted.student.label(), submitted.files.clone()))
+            .collect();
+        assert_eq!(
+            submitted,
+            [
+                (
+                    "2021001_张三".into(),
+                    vec![PathBuf::from("2021001_张三.zip")]
+                ),
+                ("2021003_李四".into(), vec![PathBuf::from("2021003_李四")]),
+                ("王五".into(), vec![PathBuf::from("王五.rar")]),
+            ]
+        );
+        let unchecked: Vec<(&str, &str)> = report
+            .unchecked
+            .iter()
+            .map(|file| (file.name.as_str(), file.reason.as_str()))
+            .collect();
+        assert_eq!(
+            unchecked,
+            [(
+                "王五.rar",
+                "不支持查看 rar 压缩包中的文件，未检查必需的文件"
+            )]
+        );
+        let invalid: Vec<(String, String)> = report
+            .invalid
+            .iter()
+            .map(|invalid| (invalid.student.label(), invalid.reason()))
+            .collect();
+        assert_eq!(
+            invalid,
+            [(
+                "2021002_张三".into(),
+                "2021002_张三.zip: 缺少必需的文件: src/".into()
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_take_latest_modified_time_as_submission_time() -> Result<()> {
+        // Given
+        let tmp_dir = submit(&["2021001_张三/v1.c", "2021001_张三/v2.c", "李四.pdf"])?;
+        let touch = |file: &str, time: &str| -> Result<DateTime<Local>> {
+            let time = DateTime::parse_from_rfc3339(time)?.with_timezone(&Local);
+            fs::File::options()
+                .write(true)
+                .open(tmp_dir.path().join(file))?
+                .set_modified(time.into())?;
+            Ok(time)
+        };
+        touch("2021001_张三/v1.c", "2024-10-01T20:00:00+08:00")?;
+        let latest = touch("2021001_张三/v2.c", "2024-10-02T09:30:00+08:00")?;
+        let on_time = touch("李四.pdf", "2024-10-01T12:00:00+08:00")?;
+
+        // When
+        let report = check_submissions(
+            &stu_config(),
+            Some(tmp_dir.path()),
+            None,
+            &ContentRules::default(),
+            &ScanOptions::default(),
+        )?;
+
+        // Then
+        let submitted: Vec<(String, Option<DateTime<Local>>)> = report
+            .submitted
+            .iter()
+            .map(|submitted| (submitted.student.label(), submitted.submitted_at))
+            .collect();
+        assert_eq!(
+            submitted,
+            [
+                ("2021001_张三".to_string(), Some(latest)),
+                ("2021003_李四".to_string(), Some(on_time)),
+            ]
+        );
 
-    Ok(missing)
+        Ok(())
+    }
 }
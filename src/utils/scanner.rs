@@ -0,0 +1,295 @@
+use crate::config::app_config::CheckConfig;
+use crate::email::receive_state::STATE_FILE_NAME;
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// 提交目录扫描规则
+#[derive(Debug)]
+pub struct ScanOptions {
+    /// 是否递归扫描子目录
+    recursive: bool,
+
+    /// 递归扫描的最大深度，检查目录下的直接条目深度为1
+    max_depth: usize,
+
+    /// 只检查匹配这些规则的文件，为空时检查所有文件
+    include: Option<GlobSet>,
+
+    /// 忽略匹配这些规则的文件和目录
+    exclude: GlobSet,
+}
+
+/// 扫描到的一份提交
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedEntry {
+    /// 相对于检查目录的路径
+    pub path: PathBuf,
+
+    /// 用于匹配学生的名称：条目自身的名称在前，其后依次为所在目录的名称（由近及远）
+    pub names: Vec<String>,
+
+    /// 是否为目录，只有非递归扫描时目录才会作为一份提交
+    pub is_dir: bool,
+
+    /// 最后修改时间，目录取其中最新的文件的修改时间
+    pub modified: Option<SystemTime>,
+}
+
+impl ScanOptions {
+    /// 根据检查配置创建扫描规则
+    ///
+    /// # param
+    /// * `check_config` - 作业检查配置
+    ///
+    /// # return
+    /// * `Result<Self>` - 扫描规则或通配符错误
+    pub fn from_config(check_config: &CheckConfig) -> Result<Self> {
+        let include = match check_config.include.is_empty() {
+            true => None,
+            false => Some(build_glob_set(&check_config.include)?),
+        };
+
+        Ok(Self {
+            recursive: check_config.recursive,
+            max_depth: check_config.max_depth,
+            include,
+            exclude: build_glob_set(&check_config.exclude)?,
+        })
+    }
+
+    /// 判断是否忽略该条目
+    ///
+    /// 规则同时与条目名称和相对路径匹配，任一匹配即可。接收邮件时写入的状态文件不是提交，总是忽略
+    fn is_excluded(&self, relative: &Path, is_dir: bool) -> bool {
+        let name = relative.file_name().map(Path::new).unwrap_or(relative);
+        let matches = |set: &GlobSet| set.is_match(name) || set.is_match(relative);
+
+        name == Path::new(STATE_FILE_NAME)
+            || matches(&self.exclude)
+            || (!is_dir && self.include.as_ref().is_some_and(|set| !matches(set)))
+    }
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self::from_config(&CheckConfig::default()).expect("默认扫描规则有效")
+    }
+}
+
+/// 校验并编译通配符规则
+///
+/// # param
+/// * `patterns` - 通配符规则，如 `~$*`、`__MACOSX`、`*.{pdf,docx}`
+pub fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern).with_context(|| format!("无效的通配符: {}", pattern))?);
+    }
+
+    Ok(builder.build()?)
+}
+
+/// 扫描检查目录中的提交
+///
+/// 非递归模式下，检查目录下的每个文件或目录都是一份提交；递归模式下，`max_depth` 层以内的每个文件都是一份提交，
+/// 文件名与所在目录名都可以用来匹配学生，如 `张三/main.c`。被忽略的目录不会继续扫描
+///
+/// # param
+/// * `dir` - 检查目录
+/// * `options` - 扫描规则
+///
+/// # return
+/// * `Result<Vec<ScannedEntry>>` - 按路径排序的提交
+pub fn scan(dir: &Path, options: &ScanOptions) -> Result<Vec<ScannedEntry>> {
+    let mut entries = Vec::new();
+    scan_dir(dir, Path::new(""), 1, options, &mut entries)?;
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(entries)
+}
+
+/// 扫描一层目录
+fn scan_dir(
+    root: &Path,
+    relative_dir: &Path,
+    depth: usize,
+    options: &ScanOptions,
+    entries: &mut Vec<ScannedEntry>,
+) -> Result<()> {
+    let dir = root.join(relative_dir);
+    let read_dir =
+        fs::read_dir(&dir).with_context(|| format!("无法读取目录: {}", dir.display()))?;
+
+    for entry in read_dir.filter_map(|entry| entry.ok()) {
+        let relative = relative_dir.join(entry.file_name());
+        let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+        if relative.to_str().is_none() || options.is_excluded(&relative, is_dir) {
+            continue;
+        }
+
+        if options.recursive && is_dir {
+            if depth < options.max_depth {
+                scan_dir(root, &relative, depth + 1, options, entries)?;
+            }
+            continue;
+        }
+
+        let names = relative
+            .iter()
+            .rev()
+            .filter_map(|name| name.to_str().map(str::to_string))
+            .collect();
+        let modified = match is_dir {
+            true => newest_modified(root, &relative, options),
+            false => entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+        };
+        entries.push(ScannedEntry {
+            path: relative,
+            names,
+            is_dir,
+            modified,
+        });
+    }
+
+    Ok(())
+}
+
+/// 列出目录中未被忽略的所有文件，用于检查文件夹提交的内容
+///
+/// # param
+/// * `root` - 检查目录
+/// * `relative_dir` - 相对于检查目录的目录路径
+/// * `options` - 扫描规则
+///
+/// # return
+/// * `Vec<PathBuf>` - 相对于检查目录、按路径排序的文件
+pub fn files_in(root: &Path, relative_dir: &Path, options: &ScanOptions) -> Vec<PathBuf> {
+    fn walk(root: &Path, relative_dir: &Path, options: &ScanOptions, files: &mut Vec<PathBuf>) {
+        let Ok(read_dir) = fs::read_dir(root.join(relative_dir)) else {
+            return;
+        };
+        for entry in read_dir.filter_map(|entry| entry.ok()) {
+            let relative = relative_dir.join(entry.file_name());
+            let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+            if options.is_excluded(&relative, is_dir) {
+                continue;
+            }
+            match is_dir {
+                true => walk(root, &relative, options, files),
+                false => files.push(relative),
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    walk(root, relative_dir, options, &mut files);
+    files.sort();
+    files
+}
+
+/// 目录中最新的文件的修改时间，忽略被排除的文件
+fn newest_modified(root: &Path, relative_dir: &Path, options: &ScanOptions) -> Option<SystemTime> {
+    fs::read_dir(root.join(relative_dir))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let relative = relative_dir.join(entry.file_name());
+            let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+            if options.is_excluded(&relative, is_dir) {
+                None
+            } else if is_dir {
+                newest_modified(root, &relative, options)
+            } else {
+                entry
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            }
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn create(root: &Path, files: &[&str]) -> Result<()> {
+        for file in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, "")?;
+        }
+        Ok(())
+    }
+
+    fn paths(entries: &[ScannedEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .map(|entry| entry.path.to_string_lossy().replace('\\', "/"))
+            .collect()
+    }
+
+    #[test]
+    fn should_scan_recursively_within_max_depth() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        create(
+            tmp_dir.path(),
+            &[
+                "张三/main.c",
+                "张三/.DS_Store",
+                "张三/~$报告.docx",
+                "1班/李四/src/deep/main.c",
+                "1班/李四/报告.pdf",
+                "__MACOSX/张三/._main.c",
+                "王五.pdf",
+            ],
+        )?;
+        let options = ScanOptions {
+            recursive: true,
+            max_depth: 3,
+            ..Default::default()
+        };
+
+        // When
+        let entries = scan(tmp_dir.path(), &options)?;
+
+        // Then
+        assert_eq!(
+            paths(&entries),
+            ["1班/李四/报告.pdf", "张三/main.c", "王五.pdf"]
+        );
+        assert_eq!(entries[0].names, ["报告.pdf", "李四", "1班"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_scan_top_level_entries_with_include_rules() -> Result<()> {
+        // Given
+        let tmp_dir = tempdir()?;
+        create(
+            tmp_dir.path(),
+            &["张三/main.c", "李四.pdf", "王五.txt", "Thumbs.db"],
+        )?;
+        let options = ScanOptions {
+            include: Some(build_glob_set(&["*.pdf".to_string()])?),
+            ..Default::default()
+        };
+
+        // When
+        let entries = scan(tmp_dir.path(), &options)?;
+
+        // Then
+        assert_eq!(paths(&entries), ["张三", "李四.pdf"]);
+
+        Ok(())
+    }
+}